@@ -18,7 +18,27 @@ pub struct BaseballGame {
     on_first: bool,
     on_second: bool,
     on_third: bool,
+    #[serde(default)]
+    pitcher: Option<String>,
+    #[serde(default)]
+    batter: Option<String>,
+    #[serde(default)]
+    pitch_count: Option<u16>, // Pitches thrown by the current pitcher
+    #[serde(default)]
+    last_play: Option<String>,
+    #[serde(default)]
+    line_score: Vec<Inning>,
 }
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Inning {
+    away: Option<u8>,
+    home: Option<u8>, // Empty until the bottom of the inning is played
+}
+
+const LINE_SCORE_INNINGS: usize = 9; // Extra innings push the earliest ones off the panel
+const INNING_COLUMN_WIDTH: i32 = 5;
+
 impl Ord for BaseballGame {
     fn cmp(&self, other: &Self) -> Ordering {
         self.common.cmp(&other.common)
@@ -42,6 +62,117 @@ impl game::Sport for BaseballGame {
     fn get_common(&self) -> &game::CommonGameData {
         &self.common
     }
+
+    fn get_situation(&self) -> game::Situation {
+        game::Situation {
+            runner_in_scoring_position: self.on_second || self.on_third,
+            ..Default::default()
+        }
+    }
+}
+
+impl BaseballGame {
+    fn has_details(&self) -> bool {
+        self.pitcher.is_some()
+            || self.batter.is_some()
+            || self.last_play.is_some()
+            || !self.line_score.is_empty()
+    }
+
+    // The innings that fit on the panel, numbered from 1
+    fn get_visible_innings(&self) -> impl Iterator<Item = (usize, &Inning)> {
+        let skipped = self.line_score.len().saturating_sub(LINE_SCORE_INNINGS);
+        self.line_score
+            .iter()
+            .enumerate()
+            .skip(skipped)
+            .map(|(i, inning)| (i + 1, inning))
+    }
+
+    // Pitcher and pitch count, batter, the last play and the line score
+    fn draw_details(&self, canvas: &mut rpi_led_matrix::LedCanvas, font: &matrix::Font) {
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(120, 120, 120);
+        let yellow = common::new_color(255, 255, 0);
+        let row_height = font.dimensions.height + 1;
+
+        let people = [("P", &self.pitcher), ("AB", &self.batter)];
+        for (i, (label, name)) in people.iter().enumerate() {
+            let baseline = (i as i32 + 1) * row_height;
+            canvas.draw_text(&font.led_font, label, 1, baseline, &grey, 0, false);
+            if let Some(name) = name {
+                canvas.draw_text(
+                    &font.led_font,
+                    &name.to_ascii_uppercase(),
+                    11,
+                    baseline,
+                    &white,
+                    0,
+                    false,
+                );
+            }
+        }
+        if let Some(pitch_count) = self.pitch_count {
+            let pitch_text = format!("{}P", pitch_count);
+            canvas.draw_text(
+                &font.led_font,
+                &pitch_text,
+                width - 1 - font.get_text_dimensions(&pitch_text).width,
+                row_height,
+                &yellow,
+                0,
+                false,
+            );
+        }
+        if let Some(last_play) = &self.last_play {
+            matrix::draw_marquee(
+                canvas,
+                font,
+                &last_play.to_ascii_uppercase(),
+                1,
+                3 * row_height,
+                &grey,
+            );
+        }
+
+        // One column per inning, the inning in progress is highlighted
+        let teams = [
+            (&self.common.away_team, true),
+            (&self.common.home_team, false),
+        ];
+        for (row, (team, is_away)) in teams.iter().enumerate() {
+            let baseline = 3 * row_height + 1 + (row as i32 + 1) * row_height;
+            canvas.draw_text(
+                &font.led_font,
+                &team.abbreviation.to_ascii_uppercase(),
+                1,
+                baseline,
+                &white,
+                0,
+                false,
+            );
+            for (column, (number, inning)) in self.get_visible_innings().enumerate() {
+                let runs = if *is_away { inning.away } else { inning.home };
+                let runs = match runs {
+                    Some(runs) => runs,
+                    None => continue,
+                };
+                let is_current = number == self.line_score.len()
+                    && self.is_inning_top == *is_away
+                    && self.common.status == game::GameStatus::Active;
+                canvas.draw_text(
+                    &font.led_font,
+                    &runs.to_string(),
+                    16 + column as i32 * INNING_COLUMN_WIDTH,
+                    baseline,
+                    if is_current { &yellow } else { &grey },
+                    0,
+                    false,
+                );
+            }
+        }
+    }
 }
 
 fn get_base_asset(on_base: bool, pixels_book: &matrix::PixelBook) -> &common::Pixels {
@@ -60,6 +191,10 @@ impl aws_screen::AWSScreenType for BaseballGame {
         timezone: &Tz,
     ) {
         let font = &font_book.font4x6;
+        if game::show_detail_page(&self.common, self.has_details()) {
+            self.draw_details(canvas, font);
+            return;
+        }
         game::draw_scoreboard(canvas, font, &self.common, 1, (2, 2));
         let white = common::new_color(255, 255, 255);
         let ordinal_text = &self.common.get_ordinal_text(timezone);
@@ -156,3 +291,51 @@ impl aws_screen::AWSScreenType for BaseballGame {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseball_details() {
+        let data = r#"{
+            "common": {
+                "home_team": {"id": "110", "display_name": "Orioles", "abbreviation": "BAL", "primary_color": "df4601", "secondary_color": "27251f"},
+                "away_team": {"id": "120", "display_name": "Nationals", "abbreviation": "WSH", "primary_color": "ab0003", "secondary_color": "14225a"},
+                "away_score": 3, "home_score": 4, "status": "ACTIVE", "ordinal": "Top 11th",
+                "start_time": "2020-08-07T22:05:00Z", "sport_id": 1, "id": 630879
+            },
+            "balls": 1, "outs": 2, "strikes": 2, "is_inning_top": true,
+            "on_first": false, "on_second": false, "on_third": false
+        }"#;
+        let game: BaseballGame = serde_json::from_str(data).unwrap();
+        assert!(!game.has_details());
+
+        let data = r#"{
+            "common": {
+                "home_team": {"id": "110", "display_name": "Orioles", "abbreviation": "BAL", "primary_color": "df4601", "secondary_color": "27251f"},
+                "away_team": {"id": "120", "display_name": "Nationals", "abbreviation": "WSH", "primary_color": "ab0003", "secondary_color": "14225a"},
+                "away_score": 3, "home_score": 4, "status": "ACTIVE", "ordinal": "Top 11th",
+                "start_time": "2020-08-07T22:05:00Z", "sport_id": 1, "id": 630879
+            },
+            "balls": 1, "outs": 2, "strikes": 2, "is_inning_top": true,
+            "on_first": false, "on_second": false, "on_third": false,
+            "pitcher": "Finnegan", "batter": "Mancini", "pitch_count": 23,
+            "last_play": "Santander struck out swinging",
+            "line_score": [
+                {"away": 1, "home": 0}, {"away": 0, "home": 2}, {"away": 0, "home": 0},
+                {"away": 2, "home": 0}, {"away": 0, "home": 0}, {"away": 0, "home": 1},
+                {"away": 0, "home": 0}, {"away": 0, "home": 0}, {"away": 0, "home": 0},
+                {"away": 0, "home": 1}, {"away": 0}
+            ]
+        }"#;
+        let game: BaseballGame = serde_json::from_str(data).unwrap();
+        assert!(game.has_details());
+        let innings: Vec<usize> = game
+            .get_visible_innings()
+            .map(|(number, _)| number)
+            .collect();
+        assert_eq!(innings, (3..=11).collect::<Vec<usize>>());
+        assert_eq!(game.line_score[10].home, None);
+    }
+}
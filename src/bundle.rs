@@ -0,0 +1,278 @@
+// Export and import a complete device configuration as a single JSON bundle, so a board can be
+// restored after replacing the SD card
+use crate::common::{self, CustomMessage, ProfileList, ScoreboardSettingsData};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+const SECRETS_PATH: &str = "secrets.txt";
+const SETTINGS_PATH: &str = "scoreboard_settings.json";
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct ConfigBundle {
+    pub format_version: u32,
+    pub scoreboard_version: String,
+    pub settings: ScoreboardSettingsData,
+    #[serde(default)]
+    pub custom_message: Option<CustomMessage>,
+    #[serde(default)]
+    pub profiles: ProfileList,
+    #[serde(default)]
+    pub secrets: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub scoreboard_version: String,
+    pub changes: Vec<String>,
+    #[serde(default)]
+    pub secrets_ignored: bool, // Secrets are only imported from the command line, never over HTTP
+}
+
+pub fn export_bundle(root_path: &Path, include_secrets: bool) -> io::Result<ConfigBundle> {
+    let settings_string = fs::read_to_string(root_path.join(SETTINGS_PATH))?;
+    let settings: ScoreboardSettingsData = serde_json::from_str(&settings_string)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let custom_message = if root_path.join(common::MESSAGE_PATH).exists() {
+        Some(common::read_custom_message(root_path))
+    } else {
+        None
+    };
+    let profiles = fs::read_to_string(root_path.join(common::PROFILES_PATH))
+        .ok()
+        .and_then(|profiles_string| serde_json::from_str(&profiles_string).ok())
+        .unwrap_or_default();
+    let secrets = if include_secrets {
+        Some(fs::read_to_string(root_path.join(SECRETS_PATH))?)
+    } else {
+        None
+    };
+    Ok(ConfigBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        scoreboard_version: self_update::cargo_crate_version!().to_string(),
+        settings,
+        custom_message,
+        profiles,
+        secrets,
+    })
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+// Reject bundles this build does not know how to read
+pub fn check_version(bundle: &ConfigBundle) -> Result<(), String> {
+    if bundle.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!(
+            "Bundle format {} is newer than supported format {}",
+            bundle.format_version, BUNDLE_FORMAT_VERSION
+        ));
+    }
+    let current_version = self_update::cargo_crate_version!();
+    if parse_version(&bundle.scoreboard_version) > parse_version(current_version) {
+        return Err(format!(
+            "Bundle was exported by scoreboard {}, update from {} before importing",
+            bundle.scoreboard_version, current_version
+        ));
+    }
+    Ok(())
+}
+
+// Device specific state stays with the board, everything the user configured comes from the bundle
+pub fn merge_settings(
+    current: &ScoreboardSettingsData,
+    imported: &ScoreboardSettingsData,
+) -> ScoreboardSettingsData {
+    ScoreboardSettingsData {
+        setup_state: current.setup_state,
+        mac_address: current.mac_address.clone(),
+        version: current.version,
+        startup_power: current.startup_power,
+        startup_auto_power: current.startup_auto_power,
+        ..imported.clone()
+    }
+}
+
+fn describe_change<T: PartialEq + std::fmt::Debug>(
+    changes: &mut Vec<String>,
+    name: &str,
+    current: &T,
+    imported: &T,
+) {
+    if current != imported {
+        changes.push(format!("{}: {:?} -> {:?}", name, current, imported));
+    }
+}
+
+// List everything importing the bundle would change on this board
+pub fn describe_import(
+    bundle: &ConfigBundle,
+    root_path: &Path,
+    current_settings: &ScoreboardSettingsData,
+) -> Vec<String> {
+    let mut changes = Vec::new();
+    let settings = merge_settings(current_settings, &bundle.settings);
+    describe_change(&mut changes, "name", &current_settings.name, &settings.name);
+    describe_change(
+        &mut changes,
+        "timezone",
+        &current_settings.timezone.to_string(),
+        &settings.timezone.to_string(),
+    );
+    describe_change(
+        &mut changes,
+        "active_screen",
+        &current_settings.active_screen,
+        &settings.active_screen,
+    );
+    describe_change(&mut changes, "brightness", &current_settings.brightness, &settings.brightness);
+    describe_change(
+        &mut changes,
+        "rotation_time",
+        &current_settings.rotation_time,
+        &settings.rotation_time,
+    );
+    describe_change(
+        &mut changes,
+        "auto_power_mode",
+        &current_settings.auto_power_mode,
+        &settings.auto_power_mode,
+    );
+    describe_change(&mut changes, "split_view", &current_settings.split_view, &settings.split_view);
+    if current_settings.favorite_teams != settings.favorite_teams {
+        changes.push(format!(
+            "favorite_teams: {} -> {} teams",
+            current_settings.favorite_teams.len(),
+            settings.favorite_teams.len()
+        ));
+    }
+    if current_settings.screens != settings.screens {
+        changes.push("screens: replaced".to_string());
+    }
+
+    if let Some(custom_message) = &bundle.custom_message {
+        if &common::read_custom_message(root_path) != custom_message {
+            changes.push("custom_message: replaced".to_string());
+        }
+    }
+
+    let current_profiles: ProfileList = fs::read_to_string(root_path.join(common::PROFILES_PATH))
+        .ok()
+        .and_then(|profiles_string| serde_json::from_str(&profiles_string).ok())
+        .unwrap_or_default();
+    bundle.profiles.profiles.iter().for_each(|profile| {
+        match current_profiles
+            .profiles
+            .iter()
+            .find(|current| current.name == profile.name)
+        {
+            None => changes.push(format!("profiles: add {}", profile.name)),
+            Some(current) if current != profile => {
+                changes.push(format!("profiles: replace {}", profile.name))
+            }
+            _ => {}
+        }
+    });
+    current_profiles
+        .profiles
+        .iter()
+        .filter(|current| {
+            !bundle
+                .profiles
+                .profiles
+                .iter()
+                .any(|profile| profile.name == current.name)
+        })
+        .for_each(|current| changes.push(format!("profiles: remove {}", current.name)));
+
+    if let Some(secrets) = &bundle.secrets {
+        match fs::read_to_string(root_path.join(SECRETS_PATH)) {
+            Ok(current) if &current == secrets => {}
+            _ => changes.push("secrets.txt: replaced".to_string()),
+        }
+    }
+    changes
+}
+
+// Write the bundle's files into root_path. Settings are written by the caller, since the running
+// scoreboard owns scoreboard_settings.json
+pub fn write_bundle_files(
+    bundle: &ConfigBundle,
+    root_path: &Path,
+    file_writes: &common::FileWrites,
+) -> io::Result<()> {
+    if let Some(custom_message) = &bundle.custom_message {
+        common::write_custom_message(root_path, custom_message, file_writes)?;
+    }
+    file_writes.write(
+        &root_path.join(common::PROFILES_PATH),
+        &serde_json::to_string_pretty(&bundle.profiles)?,
+    )?;
+    if let Some(secrets) = &bundle.secrets {
+        file_writes.write(&root_path.join(SECRETS_PATH), secrets)?;
+    }
+    Ok(())
+}
+
+// Import straight into root_path while the scoreboard is not running, used by the CLI
+pub fn import_offline(
+    bundle: &ConfigBundle,
+    root_path: &Path,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    check_version(bundle)?;
+    let settings_path = root_path.join(SETTINGS_PATH);
+    let current_settings: ScoreboardSettingsData = fs::read_to_string(&settings_path)
+        .ok()
+        .and_then(|settings_string| serde_json::from_str(&settings_string).ok())
+        .unwrap_or_else(|| bundle.settings.clone());
+    let changes = describe_import(bundle, root_path, &current_settings);
+    if !dry_run {
+        let file_writes = common::FileWrites::default();
+        write_bundle_files(bundle, root_path, &file_writes).map_err(|e| e.to_string())?;
+        let settings = merge_settings(&current_settings, &bundle.settings);
+        file_writes
+            .write(
+                &settings_path,
+                &serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?,
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(ImportReport {
+        dry_run,
+        scoreboard_version: bundle.scoreboard_version.clone(),
+        changes,
+        secrets_ignored: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newer_bundle_rejected() {
+        let settings: ScoreboardSettingsData =
+            serde_json::from_str(include_str!("../assets/demo/scoreboard_settings.json")).unwrap();
+        let mut bundle = ConfigBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            scoreboard_version: "99.0.0".to_string(),
+            settings,
+            custom_message: None,
+            profiles: ProfileList::default(),
+            secrets: None,
+        };
+        assert!(check_version(&bundle).is_err());
+        bundle.scoreboard_version = "1.0.0".to_string();
+        assert!(check_version(&bundle).is_ok());
+        bundle.format_version = BUNDLE_FORMAT_VERSION + 1;
+        assert!(check_version(&bundle).is_err());
+    }
+}
@@ -7,6 +7,7 @@ use std::thread::sleep;
 use std::io;
 use users::{get_current_uid, get_user_by_uid};
 const LONG_PRESS_DURATION: Duration = Duration::from_secs(5);
+const MEDIUM_PRESS_DURATION: Duration = Duration::from_secs(2);
 const DOUBLE_PRESS_WINDOW: Duration = Duration::from_millis(250);
 pub struct ButtonHandler {
     command_sender: mpsc::Sender<common::MatrixCommand>,
@@ -19,8 +20,8 @@ enum ButtonState {
 }
 
 impl ButtonHandler {
-    pub fn new(command_sender: mpsc::Sender<common::MatrixCommand>) -> ButtonHandler {
-        let pin = sysfs_gpio::Pin::new(25);
+    pub fn new(command_sender: mpsc::Sender<common::MatrixCommand>, pin: u64) -> ButtonHandler {
+        let pin = sysfs_gpio::Pin::new(pin);
         if pin.is_exported() {
             info!("Unexporting pin");
             pin.unexport().expect("Faile to unexport pin");
@@ -81,6 +82,9 @@ impl ButtonHandler {
                                 if now.duration_since(lpt) > LONG_PRESS_DURATION {
                                     self.handle_long_press();
                                     last_release_time = None;
+                                } else if now.duration_since(lpt) > MEDIUM_PRESS_DURATION {
+                                    self.handle_medium_press();
+                                    last_release_time = None;
                                 } else if let Some(lrt) = last_release_time {
                                     if now.duration_since(lrt) < DOUBLE_PRESS_WINDOW {
                                         self.handle_double_press();
@@ -118,6 +122,13 @@ impl ButtonHandler {
             })
             .unwrap()
     }
+    // Switch to the next settings profile, or toggle power when there are none
+    fn handle_medium_press(&self) {
+        info!("MEDIUM PRESS");
+        self.command_sender
+            .send(common::MatrixCommand::CycleProfile())
+            .unwrap()
+    }
     // Send display on/off command
     fn handle_single_press(&self) {
         info!("SHORT PRESS");
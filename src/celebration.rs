@@ -0,0 +1,195 @@
+// Full screen animations played when a favorite team scores
+use crate::common::{self, ScreenId};
+use crate::game;
+use crate::matrix;
+use crate::score_events::{ScoreEvent, ScoreEventKind};
+
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+const CELEBRATION_TIME: Duration = Duration::from_secs(5);
+pub const CELEBRATION_COOLDOWN: Duration = Duration::from_secs(90); // Per team, so a flurry of scores doesn't loop
+const FIREWORK_COUNT: usize = 10;
+const FIREWORK_MILLIS: u128 = 900; // How long a single burst lasts
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Style {
+    Flash,     // The whole panel flashes between team colors, like a goal horn
+    Sweep,     // A band of team color sweeps across the panel
+    Fireworks, // Bursts in team colors
+}
+
+pub struct Celebration {
+    pub sport_id: ScreenId,
+    pub game_id: usize,
+    style: Style,
+    text: String,
+    primary_color: rpi_led_matrix::LedColor,
+    secondary_color: rpi_led_matrix::LedColor,
+    fireworks: Vec<(f32, f32)>, // Centers of each burst as fractions of the panel size
+    start: Instant,
+}
+
+impl Celebration {
+    // Sports without a celebration return None. `walk_off` is set when the score also ended the game
+    pub fn for_event(
+        game: &dyn game::Sport,
+        event: &ScoreEvent,
+        walk_off: bool,
+    ) -> Option<Celebration> {
+        let (team_id, points) = match event.kind {
+            ScoreEventKind::ScoreChanged { team_id, points } => (team_id, points),
+            _ => return None,
+        };
+        let (style, text) = match event.sport_id {
+            ScreenId::Hockey | ScreenId::Soccer => (Style::Flash, "GOAL!".to_string()),
+            ScreenId::Football | ScreenId::CollegeFootball if points >= 6 => {
+                (Style::Sweep, "TOUCHDOWN".to_string())
+            }
+            ScreenId::Football | ScreenId::CollegeFootball => (Style::Flash, "SCORE!".to_string()),
+            ScreenId::Baseball if walk_off => (Style::Fireworks, "WALK-OFF".to_string()),
+            ScreenId::Baseball if points == 1 => (Style::Flash, "RUN!".to_string()),
+            ScreenId::Baseball => (Style::Flash, format!("{} RUNS!", points)),
+            _ => return None,
+        };
+        let common = game.get_common();
+        let team = if common.home_team.id == team_id {
+            &common.home_team
+        } else {
+            &common.away_team
+        };
+        let mut rng = rand::thread_rng();
+        Some(Celebration {
+            sport_id: event.sport_id,
+            game_id: event.game_id,
+            style,
+            text,
+            primary_color: team.primary_color.clone(),
+            secondary_color: team.secondary_color.clone(),
+            fireworks: (0..FIREWORK_COUNT)
+                .map(|_| (rng.gen_range(0.125, 0.875), rng.gen_range(0.125, 0.625)))
+                .collect(),
+            start: Instant::now(),
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.start.elapsed() > CELEBRATION_TIME
+    }
+
+    pub fn draw(&self, canvas: &mut rpi_led_matrix::LedCanvas, font_book: &matrix::FontBook) {
+        let (width, height) = canvas.canvas_size();
+        let elapsed = self.start.elapsed().as_millis();
+        match self.style {
+            Style::Flash => {
+                let (background, foreground) = match (elapsed / 250) % 2 {
+                    0 => (&self.primary_color, &self.secondary_color),
+                    _ => (&self.secondary_color, &self.primary_color),
+                };
+                matrix::draw_rectangle(canvas, (0, 0), (width, height), background);
+                matrix::draw_text_centered_horizontally(
+                    canvas,
+                    &self.text,
+                    height / 2,
+                    &font_book.font7x13,
+                    foreground,
+                );
+            }
+            Style::Sweep => {
+                let band_width = 16;
+                let x = (elapsed / 12) as i32 % (width + band_width) - band_width;
+                matrix::draw_rectangle(
+                    canvas,
+                    (x, 0),
+                    (x + band_width, height),
+                    &self.primary_color,
+                );
+                matrix::draw_text_centered_horizontally(
+                    canvas,
+                    &self.text,
+                    height / 2,
+                    &font_book.font5x8,
+                    &self.secondary_color,
+                );
+            }
+            Style::Fireworks => {
+                let white = common::new_color(255, 255, 255);
+                let spacing = CELEBRATION_TIME.as_millis() / FIREWORK_COUNT as u128;
+                for (i, (x_fraction, y_fraction)) in self.fireworks.iter().enumerate() {
+                    let burst_start = i as u128 * spacing;
+                    if elapsed < burst_start || elapsed > burst_start + FIREWORK_MILLIS {
+                        continue;
+                    }
+                    let (x, y) = (
+                        (x_fraction * width as f32) as i32,
+                        (y_fraction * height as f32) as i32,
+                    );
+                    let radius = ((elapsed - burst_start) * 10 / FIREWORK_MILLIS) as f32;
+                    let color = match i % 2 {
+                        0 => &self.primary_color,
+                        _ => &self.secondary_color,
+                    };
+                    for spoke in 0..8 {
+                        let angle = spoke as f32 * std::f32::consts::PI / 4.0;
+                        canvas.set(
+                            x + (radius * angle.cos()) as i32,
+                            y + (radius * angle.sin()) as i32,
+                            color,
+                        );
+                    }
+                }
+                matrix::draw_text_centered_horizontally(
+                    canvas,
+                    &self.text,
+                    height - 6,
+                    &font_book.font5x8,
+                    &white,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic::GenericGame;
+
+    #[test]
+    fn test_celebration_style() {
+        let data = r#"{
+            "home_team": {"id": "120", "display_name": "Nationals", "abbreviation": "WSH", "primary_color": "ab0003", "secondary_color": "14225a"},
+            "away_team": {"id": "110", "display_name": "Orioles", "abbreviation": "BAL", "primary_color": "df4601", "secondary_color": "27251f"},
+            "away_score": 3,
+            "home_score": 4,
+            "status": "END",
+            "ordinal": "Bot 9th",
+            "start_time": "2020-08-07T22:05:00Z",
+            "sport_id": 1,
+            "id": 630879
+        }"#;
+        let game = GenericGame {
+            common: serde_json::from_str(data).unwrap(),
+            sport_id: 1,
+        };
+        let event = |sport_id, points| ScoreEvent {
+            sport_id,
+            game_id: 630879,
+            kind: ScoreEventKind::ScoreChanged {
+                team_id: 120,
+                points,
+            },
+        };
+
+        let walk_off = Celebration::for_event(&game, &event(ScreenId::Baseball, 1), true).unwrap();
+        assert_eq!(walk_off.style, Style::Fireworks);
+        assert_eq!(walk_off.primary_color.red, 171);
+        let touchdown =
+            Celebration::for_event(&game, &event(ScreenId::Football, 7), false).unwrap();
+        assert_eq!(touchdown.style, Style::Sweep);
+        let field_goal =
+            Celebration::for_event(&game, &event(ScreenId::Football, 3), false).unwrap();
+        assert_eq!(field_goal.text, "SCORE!");
+        assert!(Celebration::for_event(&game, &event(ScreenId::Basketball, 2), false).is_none());
+    }
+}
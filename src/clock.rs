@@ -72,7 +72,7 @@ impl matrix::ScreenProvider for Clock {
         self
     }
 
-    fn has_priority(&mut self, power_mode: &common::AutoPowerMode) -> bool {
+    fn has_priority(&mut self, _id: &common::ScreenId, power_mode: &common::AutoPowerMode) -> bool {
         info!("Auto power mode priority: {:?}", power_mode);
         power_mode == &common::AutoPowerMode::Clock
     }
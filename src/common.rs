@@ -1,16 +1,20 @@
 use std::process::Command;
 
+use crate::bundle::{ConfigBundle, ImportReport};
 use chrono_tz::Tz;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::*;
 use std::error::Error;
 use std::io;
 use std::net::Ipv4Addr;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use std::io::ErrorKind;
 
 pub const MESSAGE_PATH : &str = "custom_message.json";
+pub const PROFILES_PATH : &str = "profiles.json";
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Deserialize_repr, Serialize_repr, Copy)]
 #[repr(u16)]
 pub enum ScreenId {
@@ -21,7 +25,14 @@ pub enum ScreenId {
     Football = 4,
     CollegeFootball = 5,
     Golf = 6,
+    Soccer = 7,
+    Tennis = 8,
+    Motorsport = 9,
+    Cricket = 10,
     Clock = 50,
+    Standings = 51,
+    Countdown = 52,
+    Recap = 53,
     Reboot = 99,
     Refresh = 100,
     Setup = 101,
@@ -42,7 +53,14 @@ impl ScreenId {
             | ScreenId::CollegeBasketball
             | ScreenId::CollegeFootball
             | ScreenId::Football
-            | ScreenId::Golf => &ScreenId::Smart,
+            | ScreenId::Golf
+            | ScreenId::Soccer
+            | ScreenId::Tennis
+            | ScreenId::Motorsport
+            | ScreenId::Cricket
+            | ScreenId::Standings
+            | ScreenId::Countdown
+            | ScreenId::Recap => &ScreenId::Smart,
             _ => self,
         }
     }
@@ -59,6 +77,13 @@ impl ScreenId {
                 texts.extend(vec!["First down!", "Blue, 42..."])
             }
             ScreenId::Golf => texts.extend(vec!["Fore!"]),
+            ScreenId::Soccer => texts.extend(vec!["Kick off!", "Back of the net!"]),
+            ScreenId::Tennis => texts.extend(vec!["Love all!", "Quiet please!"]),
+            ScreenId::Motorsport => texts.extend(vec!["Lights out!", "Box, box!"]),
+            ScreenId::Cricket => texts.extend(vec!["Howzat!", "Play!"]),
+            ScreenId::Standings => texts.extend(vec!["Checking the table!"]),
+            ScreenId::Countdown => texts.extend(vec!["Checking the schedule!"]),
+            ScreenId::Recap => texts.extend(vec!["Reading the box scores!"]),
             _ => {}
         };
         texts
@@ -70,6 +95,7 @@ pub enum CommandSource {
     Webserver(),
     Button(),
     Task(),
+    File(), // An edit to a file under root_path, picked up by the file watcher
 }
 #[derive(Debug)]
 pub enum MatrixCommand {
@@ -83,7 +109,10 @@ pub enum MatrixCommand {
     },
     AutoPower(bool),
     Display(ScreenId),
-    UpdateSettings(ScoreboardSettingsData),
+    UpdateSettings {
+        source: CommandSource,
+        settings: ScoreboardSettingsData,
+    },
 
     // Setup Commands
     GetSettings(), // Fetch the settings
@@ -106,7 +135,29 @@ pub enum MatrixCommand {
         show_sync: Option<bool>,
     }, // Show sync, hide sync, or swap sync
     GetCustomMessage(),
-    SetCustomMessage(CustomMessage)
+    SetCustomMessage {
+        source: CommandSource,
+        message: CustomMessage,
+    },
+
+    // Profile Commands
+    GetProfiles(),
+    CreateProfile(Profile),
+    ActivateProfile {
+        source: CommandSource,
+        name: String,
+    },
+    CycleProfile(), // Activate the profile after the active one, or toggle power without profiles (Medium Press)
+    ScheduledProfile {
+        name: String,
+        generation: u32,
+    }, // Fired by the scheduler, ignored if the profile's schedule has changed since
+    DeleteProfile(String),
+    HideMessage(), // Clear a temporary message, like the profile name shown after a cycle
+    ImportConfig {
+        bundle: Box<ConfigBundle>,
+        dry_run: bool,
+    },
 }
 
 pub enum WebserverResponse {
@@ -123,6 +174,11 @@ pub enum WebserverResponse {
     GameAction(Arc<ScoreboardSettingsData>),
     GetCustomMessage(CustomMessage),
     SetCustomMessage(),
+    GetProfiles(ProfileList),
+    CreateProfile(Option<ProfileList>),
+    ActivateProfile(Option<Arc<ScoreboardSettingsData>>),
+    DeleteProfile(Option<ProfileList>),
+    ImportConfig(Result<ImportReport, String>),
 }
 
 pub enum ShellCommand {
@@ -153,6 +209,9 @@ pub fn color_from_slice(slice: &[u8]) -> rpi_led_matrix::LedColor {
 }
 
 pub fn color_from_string(s: &str) -> Result<rpi_led_matrix::LedColor, Box<dyn Error>> {
+    if s.len() != 6 || !s.is_ascii() {
+        return Err(format!("Invalid color {:?}", s).into());
+    }
     let get_value = |slice| u8::from_str_radix(slice, 16);
     let red = get_value(&s[0..2])?;
     let green = get_value(&s[2..4])?;
@@ -171,8 +230,8 @@ pub fn led_color_from_string<'de, D>(deserializer: D) -> Result<rpi_led_matrix::
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
-    color_from_string(s).map_err(serde::de::Error::custom)
+    let s: String = Deserialize::deserialize(deserializer)?;
+    color_from_string(&s).map_err(serde::de::Error::custom)
 }
 
 pub fn led_color_to_string<S>(color: &rpi_led_matrix::LedColor, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer{
@@ -224,22 +283,32 @@ pub enum SetupState {
 pub enum AutoPowerMode {
     Off,
     Clock,
-    CustomMessage
+    CustomMessage,
+    Countdown, // Wake up for the countdown shortly before a favorite team plays
+    Recap,     // Show yesterday's results in the morning
 }
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct ScreenSettings {
-    rotation_time: u32,
+    pub rotation_time: u32,
     subtitle: String,
-    focus_teams: Vec<u32>,
-    id: ScreenId,
-    always_rotate: bool,
+    pub focus_teams: Vec<u32>,
+    pub id: ScreenId,
+    pub always_rotate: bool,
     name: String,
+    #[serde(default = "default_celebrations")]
+    pub celebrations: bool, // Celebrate favorite teams scoring in this league
 }
 
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct FavoriteTeam {
     pub screen_id: ScreenId,
     pub team_id: u32,
+    #[serde(default = "default_celebrations")]
+    pub celebrate: bool,
+}
+
+fn default_celebrations() -> bool {
+    true
 }
 
 fn default_rotation_time() -> Duration {
@@ -290,11 +359,48 @@ pub fn deserialize_timezone<'de, D>(deserializer: D) -> Result<Tz, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
+    let s: String = Deserialize::deserialize(deserializer)?;
     let tz: Tz = s.parse().map_err(serde::de::Error::custom)?;
     Ok(tz)
 }
 
+pub fn serialize_optional_duration<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration {
+        Some(duration) => serializer.serialize_some(&duration.as_secs()),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let duration: Option<u64> = Deserialize::deserialize(deserializer)?;
+    Ok(duration.map(Duration::from_secs))
+}
+
+pub fn serialize_optional_timezone<S>(timezone: &Option<Tz>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match timezone {
+        Some(timezone) => serializer.serialize_some(&timezone.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn deserialize_optional_timezone<'de, D>(deserializer: D) -> Result<Option<Tz>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    s.map(|s| s.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
 pub struct ScoreboardSettingsData {
     #[serde(
@@ -330,6 +436,8 @@ pub struct ScoreboardSettingsData {
     pub startup_auto_power: Option<bool>,
     #[serde(default = "default_auto_power_mode")]
     pub auto_power_mode: AutoPowerMode,
+    #[serde(default)]
+    pub split_view: bool, // Tile several games at once on the smart screen
 }
 
 impl ScoreboardSettingsData {
@@ -349,10 +457,153 @@ impl ScoreboardSettingsData {
             rotation_time: other.rotation_time,
             brightness: other.brightness,
             auto_power_mode: other.auto_power_mode,
+            split_view: other.split_view,
             startup_power: self.startup_power,
             startup_auto_power: self.startup_auto_power,
         }
     }
+
+    // Catch values that parse but would leave the scoreboard in a broken state
+    pub fn validate(&self) -> Result<(), String> {
+        if self.brightness > 100 {
+            return Err(format!("Brightness {} is out of range", self.brightness));
+        }
+        if let Some(screen) = self.screens.iter().find(|screen| screen.rotation_time == 0) {
+            return Err(format!("Screen {} has no rotation time", screen.name));
+        }
+        Ok(())
+    }
+
+    pub fn get_screen_settings(&self, id: ScreenId) -> Option<&ScreenSettings> {
+        self.screens.iter().find(|screen| screen.id == id)
+    }
+
+    // A league's own rotation time, falling back to the global one
+    pub fn get_rotation_time(&self, id: ScreenId) -> Duration {
+        match self.get_screen_settings(id) {
+            Some(screen) if screen.rotation_time > 0 => {
+                Duration::from_secs(screen.rotation_time as u64)
+            }
+            _ => self.rotation_time,
+        }
+    }
+
+    // Favorite teams plus the league's own focus teams. For tennis these are player ids
+    pub fn get_focus_teams(&self, id: ScreenId) -> Vec<u32> {
+        let mut focus_teams: Vec<u32> = self
+            .favorite_teams
+            .iter()
+            .filter(|favorite_team| favorite_team.screen_id == id)
+            .map(|favorite_team| favorite_team.team_id)
+            .collect();
+        if let Some(screen) = self.get_screen_settings(id) {
+            focus_teams.extend(screen.focus_teams.iter());
+        }
+        focus_teams
+    }
+
+    // Whether a score by this team should interrupt the rotation with a celebration
+    pub fn should_celebrate(&self, id: ScreenId, team_id: u32) -> bool {
+        let league_celebrates = self
+            .get_screen_settings(id)
+            .map_or(true, |screen| screen.celebrations);
+        league_celebrates
+            && self.favorite_teams.iter().any(|favorite_team| {
+                favorite_team.screen_id == id
+                    && favorite_team.team_id == team_id
+                    && favorite_team.celebrate
+            })
+    }
+
+    // Split view only applies to the smart screen, league screens keep one game at a time
+    pub fn uses_split_view(&self) -> bool {
+        self.split_view && self.active_screen == ScreenId::Smart
+    }
+
+    pub fn apply_profile(&self, overlay: &ScoreboardSettingsOverlay) -> ScoreboardSettingsData {
+        let current = self.clone();
+        ScoreboardSettingsData {
+            timezone: overlay.timezone.unwrap_or(current.timezone),
+            active_screen: overlay.active_screen.unwrap_or(current.active_screen),
+            name: overlay.name.clone().unwrap_or(current.name),
+            screens: overlay.screens.clone().unwrap_or(current.screens),
+            screen_on: overlay.screen_on.unwrap_or(current.screen_on),
+            auto_power: overlay.auto_power.unwrap_or(current.auto_power),
+            favorite_teams: overlay
+                .favorite_teams
+                .clone()
+                .unwrap_or(current.favorite_teams),
+            rotation_time: overlay.rotation_time.unwrap_or(current.rotation_time),
+            brightness: overlay.brightness.unwrap_or(current.brightness),
+            auto_power_mode: overlay.auto_power_mode.unwrap_or(current.auto_power_mode),
+            split_view: overlay.split_view.unwrap_or(current.split_view),
+            ..current
+        }
+    }
+}
+
+// Any subset of the user facing settings, applied on top of the current settings when a profile
+// is activated. Setup state, version and startup settings are never part of a profile.
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct ScoreboardSettingsOverlay {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_timezone",
+        serialize_with = "serialize_optional_timezone"
+    )]
+    pub timezone: Option<Tz>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_screen: Option<ScreenId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screens: Option<Vec<ScreenSettings>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_on: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_power: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub favorite_teams: Option<Vec<FavoriteTeam>>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_duration",
+        serialize_with = "serialize_optional_duration"
+    )]
+    pub rotation_time: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brightness: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_power_mode: Option<AutoPowerMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_view: Option<bool>,
+}
+
+// Local time of day at which a profile activates itself, every day
+#[derive(Deserialize, Serialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ProfileSchedule {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub settings: ScoreboardSettingsOverlay,
+    #[serde(default)]
+    pub custom_message: Option<CustomMessage>,
+    #[serde(default)]
+    pub schedule: Option<ProfileSchedule>,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct ProfileList {
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
 }
 #[derive(Clone, Debug)]
 pub struct Pixels {
@@ -461,6 +712,62 @@ impl CustomMessage {
     }
 }
 
+// If a file we are about to overwrite was edited into something unparseable, keep a copy of the
+// edit next to it instead of silently clobbering it
+pub fn preserve_invalid_file<T: serde::de::DeserializeOwned>(path: &std::path::Path) {
+    if let Ok(contents) = std::fs::read_to_string(path) {
+        if let Err(e) = serde_json::from_str::<T>(&contents) {
+            let backup_path = path.with_extension("json.invalid");
+            warn!(
+                "{:?} does not parse ({}), keeping a copy at {:?}",
+                path, e, backup_path
+            );
+            let _copy_result = std::fs::copy(path, backup_path);
+        }
+    }
+}
+
+// Remembers what the scoreboard itself last wrote to each file, so the file watcher can tell
+// its own writes apart from edits made by the user
+#[derive(Clone, Default, Debug)]
+pub struct FileWrites(Arc<Mutex<HashMap<PathBuf, String>>>);
+
+impl FileWrites {
+    // Write through a temporary file and rename it into place, so a reader never sees a half
+    // written file
+    pub fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, contents)?;
+        self.0
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_string());
+        std::fs::rename(temp_path, path)
+    }
+
+    pub fn is_own_write(&self, path: &Path, contents: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap()
+            .get(path)
+            .map_or(false, |written| written == contents)
+    }
+}
+
+pub fn write_custom_message(
+    root_path: &std::path::Path,
+    message: &CustomMessage,
+    file_writes: &FileWrites,
+) -> io::Result<()> {
+    preserve_invalid_file::<CustomMessage>(&root_path.join(MESSAGE_PATH));
+    file_writes.write(
+        &root_path.join(MESSAGE_PATH),
+        &serde_json::to_string_pretty(message).map_err(|err| {
+            std::io::Error::new(ErrorKind::Other, err.to_string())
+        })?,
+    )
+}
+
 pub fn read_custom_message(root_path: &std::path::Path) -> CustomMessage {
     std::fs::File::open(root_path.join(MESSAGE_PATH)).and_then(|file| 
         serde_json::from_reader(file).map_err(|err| {
@@ -0,0 +1,349 @@
+// Operational knobs for the scoreboard process, as opposed to the user facing settings in
+// scoreboard_settings.json. Values come from scoreboard.toml in root_path, overridden by the
+// legacy environment variables, overridden by command line flags.
+use crate::common::{deserialize_duration, serialize_duration};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const CONFIG_PATH: &str = "scoreboard.toml";
+const V2_URL: &str = "https://uhoijpn7d1.execute-api.us-east-2.amazonaws.com/Prod/";
+
+// Environment variables that predate scoreboard.toml, and the config key each one sets
+const ENV_OVERRIDES: [(&str, &str); 4] = [
+    ("V2_URL", "api.url"),
+    ("SCOREBOARD_SLOWDOWN", "matrix.gpio_slowdown"),
+    ("DAILY_REBOOT", "startup.daily_reboot"),
+    ("REBOOT_TIME", "startup.reboot_time"),
+];
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct ApiConfig {
+    pub url: String,
+    pub key: Option<String>, // Falls back to secrets.txt in root_path
+}
+
+impl Default for ApiConfig {
+    fn default() -> ApiConfig {
+        ApiConfig {
+            url: V2_URL.to_string(),
+            key: None,
+        }
+    }
+}
+
+// Where scores come from, see score_source.rs
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceConfig {
+    Api,
+    Http { url: String },
+    Directory { path: PathBuf },
+    Demo,
+}
+
+impl Default for SourceConfig {
+    fn default() -> SourceConfig {
+        SourceConfig::Api
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct RefreshConfig {
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub active: Duration,
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub dormant: Duration,
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub retry_initial: Duration, // First retry after a failed fetch, doubling each time
+    #[serde(
+        deserialize_with = "deserialize_duration",
+        serialize_with = "serialize_duration"
+    )]
+    pub retry_max: Duration,
+    pub stream_url: Option<String>, // Server-sent events feed of game deltas, polling is the fallback
+}
+
+impl Default for RefreshConfig {
+    fn default() -> RefreshConfig {
+        RefreshConfig {
+            active: Duration::from_secs(60),      // 1 minute
+            dormant: Duration::from_secs(60 * 5), // 5 minutes
+            retry_initial: Duration::from_secs(10),
+            retry_max: Duration::from_secs(60 * 10), // 10 minutes
+            stream_url: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct MatrixConfig {
+    pub hardware_mapping: String,
+    pub gpio_slowdown: u32,
+}
+
+impl Default for MatrixConfig {
+    fn default() -> MatrixConfig {
+        MatrixConfig {
+            hardware_mapping: "adafruit-hat-pwm".to_string(),
+            gpio_slowdown: 3,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct ButtonConfig {
+    pub pin: u64,
+}
+
+impl Default for ButtonConfig {
+    fn default() -> ButtonConfig {
+        ButtonConfig { pin: 25 }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct WebserverConfig {
+    pub port: u16,
+}
+
+impl Default for WebserverConfig {
+    fn default() -> WebserverConfig {
+        WebserverConfig { port: 5005 }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct StartupConfig {
+    pub wait: bool,
+    pub skip_update: bool,
+    pub daily_reboot: bool,
+    pub reboot_time: u8, // Local hour of the nightly reboot
+}
+
+impl Default for StartupConfig {
+    fn default() -> StartupConfig {
+        StartupConfig {
+            wait: false,
+            skip_update: false,
+            daily_reboot: true,
+            reboot_time: 3,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+#[serde(default)]
+pub struct ScoreboardConfig {
+    pub api: ApiConfig,
+    pub source: SourceConfig,
+    pub refresh: RefreshConfig,
+    pub matrix: MatrixConfig,
+    pub button: ButtonConfig,
+    pub webserver: WebserverConfig,
+    pub startup: StartupConfig,
+}
+
+// Interpret a raw override as a TOML value, treating anything that isn't valid TOML as a string
+fn parse_override_value(raw: &str) -> toml::Value {
+    format!("value = {}", raw)
+        .parse::<toml::Value>()
+        .ok()
+        .and_then(|table| table.get("value").cloned())
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+// Set a dotted key like `matrix.gpio_slowdown`, creating tables along the way
+fn set_value(root: &mut toml::Value, key: &str, value: toml::Value) -> Result<(), String> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    let last = parts.pop().ok_or_else(|| format!("Invalid key {}", key))?;
+    let mut table = root;
+    for part in parts {
+        table = match table {
+            toml::Value::Table(map) => map
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(Default::default())),
+            _ => return Err(format!("{} is not a table in key {}", part, key)),
+        };
+    }
+    match table {
+        toml::Value::Table(map) => {
+            map.insert(last.to_string(), value);
+            Ok(())
+        }
+        _ => Err(format!("Cannot set {}", key)),
+    }
+}
+
+// Legacy variables may hold values the old code quietly ignored, so one that doesn't fit its key is
+// skipped with a warning instead of stopping the scoreboard from starting
+fn apply_env_override(value: &mut toml::Value, env_name: &str, key: &str, raw: &str) {
+    let mut candidate = value.clone();
+    let result = set_value(&mut candidate, key, parse_override_value(raw)).and_then(|_| {
+        candidate
+            .clone()
+            .try_into::<ScoreboardConfig>()
+            .map_err(|e| e.to_string())
+    });
+    match result {
+        Ok(_) => *value = candidate,
+        Err(e) => warn!("Ignoring {}={}: {}", env_name, raw, e),
+    }
+}
+
+impl ScoreboardConfig {
+    // `env_var` looks up the legacy environment variables, `overrides` are `key=value` pairs from
+    // the command line, applied last
+    pub fn load(
+        root_path: &Path,
+        env_var: impl Fn(&str) -> Option<String>,
+        overrides: &[(String, String)],
+    ) -> Result<ScoreboardConfig, Box<dyn Error>> {
+        let mut value = match fs::read_to_string(root_path.join(CONFIG_PATH)) {
+            Ok(config_string) => config_string.parse::<toml::Value>()?,
+            Err(_) => toml::Value::Table(Default::default()),
+        };
+        for (env_name, key) in ENV_OVERRIDES.iter() {
+            if let Some(raw) = env_var(env_name) {
+                apply_env_override(&mut value, env_name, key, &raw);
+            }
+        }
+        for (key, raw) in overrides {
+            set_value(&mut value, key, parse_override_value(raw))?;
+        }
+        let mut config: ScoreboardConfig = value.try_into()?;
+        config.startup.reboot_time = config.startup.reboot_time.min(23);
+        Ok(config)
+    }
+
+    pub fn get_api_key(&self, root_path: &Path) -> String {
+        match &self.api.key {
+            Some(key) => key.clone(),
+            None => {
+                let secrets_path = root_path.join("secrets.txt");
+                info!("Loading secrets from {:?}", secrets_path);
+                fs::read_to_string(&secrets_path).unwrap_or_else(|_| {
+                    panic!(
+                        "Could not read from secrets.txt at path {:?}",
+                        &secrets_path
+                    )
+                })
+            }
+        }
+    }
+
+    pub fn get_daily_reboot(&self) -> Option<u8> {
+        match self.startup.daily_reboot {
+            true => Some(self.startup.reboot_time),
+            false => None,
+        }
+    }
+
+    // The effective configuration as TOML, without the API key
+    pub fn to_printable_string(&self) -> String {
+        let mut printable = self.clone();
+        if printable.api.key.is_some() {
+            printable.api.key = Some("<redacted>".to_string());
+        }
+        toml::to_string_pretty(&printable).unwrap_or_else(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::env;
+
+    #[test]
+    fn test_overrides() {
+        let mut value: toml::Value = r#"
+            [matrix]
+            gpio_slowdown = 2
+
+            [webserver]
+            port = 8080
+        "#
+        .parse()
+        .unwrap();
+        set_value(
+            &mut value,
+            "matrix.gpio_slowdown",
+            parse_override_value("4"),
+        )
+        .unwrap();
+        set_value(
+            &mut value,
+            "api.url",
+            parse_override_value("http://10.0.0.2:5000/"),
+        )
+        .unwrap();
+        let config: ScoreboardConfig = value.try_into().unwrap();
+        assert_eq!(config.matrix.gpio_slowdown, 4);
+        assert_eq!(config.matrix.hardware_mapping, "adafruit-hat-pwm");
+        assert_eq!(config.webserver.port, 8080);
+        assert_eq!(config.api.url, "http://10.0.0.2:5000/");
+        assert_eq!(config.refresh.active, Duration::from_secs(60));
+        assert_eq!(config.source, SourceConfig::Api);
+
+        let mut value: toml::Value = r#"
+            [source]
+            type = "directory"
+            path = "/tmp/scores"
+        "#
+        .parse()
+        .unwrap();
+        set_value(&mut value, "source.type", parse_override_value("http")).unwrap();
+        set_value(
+            &mut value,
+            "source.url",
+            parse_override_value("http://localhost:8000/all_v4"),
+        )
+        .unwrap();
+        let config: ScoreboardConfig = value.try_into().unwrap();
+        assert_eq!(
+            config.source,
+            SourceConfig::Http {
+                url: "http://localhost:8000/all_v4".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_bad_env_override() {
+        let root_path = env::temp_dir().join("scoreboard_test_missing_config");
+        let env_vars: HashMap<&str, &str> = vec![
+            ("SCOREBOARD_SLOWDOWN", "abc"),
+            ("REBOOT_TIME", "300"),
+            ("DAILY_REBOOT", "maybe"),
+        ]
+        .into_iter()
+        .collect();
+        let config = ScoreboardConfig::load(
+            &root_path,
+            |name| env_vars.get(name).map(|raw| raw.to_string()),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(config.matrix.gpio_slowdown, 3);
+        assert_eq!(config.startup.reboot_time, 3);
+        assert!(config.startup.daily_reboot);
+    }
+}
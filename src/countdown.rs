@@ -0,0 +1,205 @@
+// Each favorite team's next game, with a live countdown once it's close
+use crate::common;
+use crate::game::{self, CommonGameData, GameStatus};
+use crate::matrix;
+
+use chrono::{Duration, Utc};
+use chrono_tz::Tz;
+
+const PAGE_TIME_SECONDS: i64 = 6;
+pub const WAKE_TIME_MINUTES: i64 = 60; // How long before a game the countdown auto power mode wakes the board
+
+pub struct NextGame<'a> {
+    pub team: &'a game::Team,
+    pub opponent: &'a game::Team,
+    pub is_home: bool,
+    pub common: &'a CommonGameData,
+}
+
+// Upcoming games aren't always full games, so they are parsed as common data on their own
+pub fn parse_upcoming(values: Vec<serde_json::Value>) -> Vec<CommonGameData> {
+    values
+        .into_iter()
+        .filter_map(|value| match serde_json::from_value(value) {
+            Ok(common) => Some(common),
+            Err(e) => {
+                warn!("Skipping upcoming game that failed to parse: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+// The earliest game that hasn't started for each favorite team, soonest first
+pub fn get_next_games<'a>(
+    games: &[&'a CommonGameData],
+    favorite_teams: &[common::FavoriteTeam],
+) -> Vec<NextGame<'a>> {
+    let mut next_games: Vec<NextGame> = favorite_teams
+        .iter()
+        .filter_map(|favorite_team| {
+            games
+                .iter()
+                .filter(|common| {
+                    common.status == GameStatus::Pregame
+                        && common.sport_id == favorite_team.screen_id
+                        && common.involves_team(favorite_team.team_id)
+                })
+                .min_by_key(|common| common.start_time)
+                .map(|common| {
+                    let is_home = common.home_team.id == favorite_team.team_id;
+                    let (team, opponent) = match is_home {
+                        true => (&common.home_team, &common.away_team),
+                        false => (&common.away_team, &common.home_team),
+                    };
+                    NextGame {
+                        team,
+                        opponent,
+                        is_home,
+                        common,
+                    }
+                })
+        })
+        .collect();
+    next_games.sort_by_key(|next_game| next_game.common.start_time);
+    next_games
+}
+
+pub fn should_wake(next_games: &[NextGame]) -> bool {
+    next_games.iter().any(|next_game| {
+        next_game.common.start_time - Utc::now() < Duration::minutes(WAKE_TIME_MINUTES)
+    })
+}
+
+// Pages through the favorites' next games, returns false if there is nothing to show
+pub fn draw_countdown(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    next_games: &[NextGame],
+    timezone: &Tz,
+) -> bool {
+    if next_games.is_empty() {
+        return false;
+    }
+    let page = (Utc::now().timestamp() / PAGE_TIME_SECONDS) as usize % next_games.len();
+    let next_game = &next_games[page];
+    let (width, _height) = canvas.canvas_size();
+    let white = common::new_color(255, 255, 255);
+    let grey = common::new_color(150, 150, 150);
+    let yellow = common::new_color(255, 255, 0);
+    let row_height = font.dimensions.height + 3;
+
+    // Favorite team bar
+    matrix::draw_rectangle(
+        canvas,
+        (0, 0),
+        (width, row_height),
+        &next_game.team.primary_color,
+    );
+    canvas.draw_text(
+        &font.led_font,
+        &next_game.team.display_name.to_ascii_uppercase(),
+        2,
+        font.dimensions.height + 1,
+        &next_game.team.secondary_color,
+        0,
+        false,
+    );
+    if next_games.len() > 1 {
+        let page_text = format!("{}/{}", page + 1, next_games.len());
+        canvas.draw_text(
+            &font.led_font,
+            &page_text,
+            width - 1 - font.get_text_dimensions(&page_text).width,
+            font.dimensions.height + 1,
+            &next_game.team.secondary_color,
+            0,
+            false,
+        );
+    }
+
+    let opponent_text = format!(
+        "{} {}",
+        if next_game.is_home { "VS" } else { "@" },
+        next_game.opponent.abbreviation.to_ascii_uppercase()
+    );
+    let start_time = next_game.common.start_time.with_timezone(timezone);
+    let seconds = (next_game.common.start_time - Utc::now()).num_seconds();
+    let time_text = start_time.format("%-I:%M %p").to_string();
+    // The date is dropped for a countdown once the game is within a day
+    let rows = match seconds < 60 * 60 * 24 {
+        true => vec![
+            (opponent_text, &white),
+            (time_text, &white),
+            (game::get_countdown_text(seconds), &yellow),
+        ],
+        false => vec![
+            (opponent_text, &white),
+            (
+                start_time
+                    .format("%a %b %-d")
+                    .to_string()
+                    .to_ascii_uppercase(),
+                &grey,
+            ),
+            (time_text, &white),
+        ],
+    };
+    for (i, (text, color)) in rows.iter().enumerate() {
+        canvas.draw_text(
+            &font.led_font,
+            text,
+            2,
+            (i as i32 + 2) * row_height - 1,
+            color,
+            0,
+            false,
+        );
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_games() {
+        let upcoming = parse_upcoming(vec![serde_json::json!({"sport_id": 0, "id": 4})]);
+        assert!(upcoming.is_empty());
+
+        let games: Vec<CommonGameData> = serde_json::from_str(
+            r#"[
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 0, "status": "PREGAME", "ordinal": "",
+                "start_time": "2030-01-03T01:00:00Z", "sport_id": 0, "id": 1
+            },
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 0, "status": "PREGAME", "ordinal": "",
+                "start_time": "2030-01-01T01:00:00Z", "sport_id": 0, "id": 2
+            },
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 2, "home_score": 3, "status": "END", "ordinal": "Final",
+                "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 3
+            }
+        ]"#,
+        )
+        .unwrap();
+        let favorite_teams: Vec<common::FavoriteTeam> = serde_json::from_str(
+            r#"[{"screen_id": 0, "team_id": 25}, {"screen_id": 1, "team_id": 25}]"#,
+        )
+        .unwrap();
+        let next_games = get_next_games(&games.iter().collect::<Vec<_>>(), &favorite_teams);
+        assert_eq!(next_games.len(), 1);
+        assert_eq!(next_games[0].common.id, 2);
+        assert_eq!(next_games[0].opponent.abbreviation, "STL");
+        assert!(!next_games[0].is_home);
+        assert!(!should_wake(&next_games));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::aws_screen;
+use crate::common;
+use crate::game;
+use crate::matrix;
+
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CricketBatter {
+    pub display_name: String,
+    pub runs: u16,
+    #[serde(default)]
+    pub on_strike: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CricketBowler {
+    pub display_name: String,
+    pub wickets: u8,
+    pub runs: u16,
+    pub overs: String, // Like 7.2, an over is six balls so this isn't a decimal
+}
+
+// Cricket scores are runs and wickets, so the common scores are unused and the batting side's
+// innings is described here instead
+#[derive(Deserialize, Debug, Clone)]
+pub struct CricketGame {
+    pub common: game::CommonGameData,
+    pub home_batting: bool,
+    pub runs: u16,
+    pub wickets: u8,
+    pub overs: String,
+    pub target: Option<u16>, // Only set when chasing
+    pub balls_remaining: Option<u16>,
+    pub required_run_rate: Option<f32>,
+    #[serde(default)]
+    pub batters: Vec<CricketBatter>,
+    pub bowler: Option<CricketBowler>,
+    pub status_text: Option<String>, // Like "Day 3 - Stumps - AUS lead by 142 runs" in a Test
+}
+
+impl Ord for CricketGame {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.common.cmp(&other.common)
+    }
+}
+
+impl PartialOrd for CricketGame {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for CricketGame {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.common,) == (&other.common,)
+    }
+}
+
+impl Eq for CricketGame {}
+
+impl game::Sport for CricketGame {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+}
+
+const ROW_HEIGHT: i32 = 6;
+
+// A team colored bar with the abbreviation on the left and optional text on the right
+fn draw_team_bar(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    team: &game::Team,
+    right_text: Option<&str>,
+    y_offset: i32,
+) {
+    let (width, _height) = canvas.canvas_size();
+    let baseline = y_offset + font.dimensions.height + 1;
+    matrix::draw_rectangle(
+        canvas,
+        (0, y_offset),
+        (width, y_offset + ROW_HEIGHT + 1),
+        &team.primary_color,
+    );
+    canvas.draw_text(
+        &font.led_font,
+        &team.abbreviation.to_ascii_uppercase(),
+        2,
+        baseline,
+        &team.secondary_color,
+        0,
+        false,
+    );
+    if let Some(right_text) = right_text {
+        canvas.draw_text(
+            &font.led_font,
+            right_text,
+            width - 2 - font.get_text_dimensions(right_text).width,
+            baseline,
+            &team.secondary_color,
+            0,
+            false,
+        );
+    }
+}
+
+impl CricketGame {
+    fn get_batting_team(&self) -> &game::Team {
+        match self.home_batting {
+            true => &self.common.home_team,
+            false => &self.common.away_team,
+        }
+    }
+
+    fn get_score_text(&self) -> String {
+        match self.wickets {
+            10 => self.runs.to_string(), // All out
+            wickets => format!("{}/{}", self.runs, wickets),
+        }
+    }
+
+    // Like NEED 45 OFF 38, empty unless the batting side is chasing
+    fn get_chase_text(&self) -> String {
+        match (self.target, self.balls_remaining) {
+            (Some(target), _) if target <= self.runs => String::new(),
+            (Some(target), Some(balls)) => format!("NEED {} OFF {}", target - self.runs, balls),
+            (Some(target), None) => format!("NEED {}", target - self.runs),
+            (None, _) => String::new(),
+        }
+    }
+
+    // Chase equation and any status line, scrolled through the marquee if it's too long
+    fn get_marquee_text(&self, timezone: &Tz) -> String {
+        let mut parts = vec![self.get_chase_text()];
+        match &self.status_text {
+            Some(status_text) => parts.push(status_text.to_ascii_uppercase()),
+            None => parts.push(self.common.get_ordinal_text(timezone)),
+        }
+        parts
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<String>>()
+            .join(" - ")
+    }
+
+    fn draw_batters(&self, canvas: &mut rpi_led_matrix::LedCanvas, font: &matrix::Font, y: i32) {
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(150, 150, 150);
+        for (i, batter) in self.batters.iter().take(2).enumerate() {
+            let name: String = batter
+                .display_name
+                .to_ascii_uppercase()
+                .chars()
+                .take(5)
+                .collect();
+            let text = format!(
+                "{}{} {}",
+                name,
+                if batter.on_strike { "*" } else { "" },
+                batter.runs
+            );
+            let x = match i {
+                0 => 2,
+                _ => width - 2 - font.get_text_dimensions(&text).width,
+            };
+            canvas.draw_text(
+                &font.led_font,
+                &text,
+                x,
+                y,
+                if batter.on_strike { &white } else { &grey },
+                0,
+                false,
+            );
+        }
+    }
+}
+
+impl aws_screen::AWSScreenType for CricketGame {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        let font = &font_book.font4x6;
+        let white = common::new_color(255, 255, 255);
+        let yellow = common::new_color(255, 255, 0);
+        let green = common::new_color(52, 162, 35);
+        let (width, height) = canvas.canvas_size();
+        let right_indent = width - 2;
+        let bottom_row = height - 1;
+
+        if self.common.status == game::GameStatus::Pregame {
+            draw_team_bar(canvas, font, &self.common.away_team, None, 0);
+            draw_team_bar(canvas, font, &self.common.home_team, None, ROW_HEIGHT + 2);
+            matrix::draw_marquee(
+                canvas,
+                font,
+                &self.get_marquee_text(timezone),
+                2,
+                bottom_row,
+                &white,
+            );
+            return;
+        }
+
+        draw_team_bar(
+            canvas,
+            font,
+            self.get_batting_team(),
+            Some(&self.get_score_text()),
+            0,
+        );
+
+        // Overs on the left, the target or required rate on the right
+        let overs_row = 2 * ROW_HEIGHT + 1;
+        canvas.draw_text(
+            &font.led_font,
+            &format!("{} OV", self.overs),
+            2,
+            overs_row,
+            &white,
+            0,
+            false,
+        );
+        let chase_info = match (self.required_run_rate, self.target) {
+            (Some(rate), _) if self.common.is_active_game() => Some(format!("RRR {:.2}", rate)),
+            (_, Some(target)) => Some(format!("TGT {}", target)),
+            _ => None,
+        };
+        if let Some(chase_info) = chase_info {
+            canvas.draw_text(
+                &font.led_font,
+                &chase_info,
+                right_indent - font.get_text_dimensions(&chase_info).width,
+                overs_row,
+                &yellow,
+                0,
+                false,
+            );
+        }
+
+        self.draw_batters(canvas, font, 3 * ROW_HEIGHT + 1);
+
+        if let Some(bowler) = &self.bowler {
+            let name: String = bowler
+                .display_name
+                .to_ascii_uppercase()
+                .chars()
+                .take(7)
+                .collect();
+            let figures = format!("{}-{} {}", bowler.wickets, bowler.runs, bowler.overs);
+            canvas.draw_text(
+                &font.led_font,
+                &name,
+                2,
+                4 * ROW_HEIGHT + 1,
+                &green,
+                0,
+                false,
+            );
+            canvas.draw_text(
+                &font.led_font,
+                &figures,
+                right_indent - font.get_text_dimensions(&figures).width,
+                4 * ROW_HEIGHT + 1,
+                &green,
+                0,
+                false,
+            );
+        }
+
+        let marquee_color = match self.common.status {
+            game::GameStatus::End => &yellow,
+            _ => &white,
+        };
+        matrix::draw_marquee(
+            canvas,
+            font,
+            &self.get_marquee_text(timezone),
+            2,
+            bottom_row,
+            marquee_color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cricket() {
+        let data = r#"{
+            "common": {
+                "sport_id": 10,
+                "home_team": {"id": "2", "display_name": "India", "abbreviation": "IND", "primary_color": "0a3a8c", "secondary_color": "ff9933"},
+                "away_team": {"id": "1", "display_name": "Australia", "abbreviation": "AUS", "primary_color": "00843d", "secondary_color": "ffcd00"},
+                "home_score": 0,
+                "away_score": 0,
+                "status": "ACTIVE",
+                "ordinal": "2nd Inn",
+                "start_time": "2023-11-19T08:30Z",
+                "id": 1234
+            },
+            "home_batting": true,
+            "runs": 245,
+            "wickets": 6,
+            "overs": "43.4",
+            "target": 290,
+            "balls_remaining": 38,
+            "required_run_rate": 7.1,
+            "batters": [
+                {"display_name": "Kohli", "runs": 54, "balls": 63, "on_strike": true},
+                {"display_name": "Jadeja", "runs": 9, "balls": 22}
+            ],
+            "bowler": {"display_name": "Starc", "wickets": 3, "runs": 55, "overs": "8.4"},
+            "status_text": null
+        }"#;
+
+        let game: CricketGame = serde_json::from_str(data).unwrap();
+        assert_eq!(game.get_batting_team().abbreviation, "IND");
+        assert_eq!(game.get_score_text(), "245/6");
+        assert_eq!(game.get_chase_text(), "NEED 45 OFF 38");
+        assert_eq!(
+            game.get_marquee_text(&chrono_tz::UTC),
+            "NEED 45 OFF 38 - 2nd Inn"
+        );
+        assert!(!game.batters[1].on_strike);
+    }
+}
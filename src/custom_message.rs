@@ -69,7 +69,7 @@ impl ScreenProvider for CustomMessageScreen {
         self
     }
 
-    fn has_priority(&mut self, power_mode: &common::AutoPowerMode) -> bool {
+    fn has_priority(&mut self, _id: &common::ScreenId, power_mode: &common::AutoPowerMode) -> bool {
         power_mode == &common::AutoPowerMode::CustomMessage
     }
 }
@@ -2,21 +2,37 @@ mod animation;
 mod aws_screen;
 mod baseball;
 mod basketball;
+mod bundle;
+mod celebration;
 mod clock;
 mod common;
+mod config;
+mod countdown;
+mod cricket;
 mod custom_message;
+mod diagnostics;
 mod flappy;
 mod football;
 mod game;
+mod generic;
 mod golf;
 mod hockey;
 mod matrix;
 mod message;
+mod motorsport;
 mod patch_notes;
+mod profiles;
+mod recap;
 mod scheduler;
+mod score_events;
+mod score_source;
 mod scoreboard_settings;
 mod setup_screen;
+mod soccer;
+mod split_view;
 mod sport;
+mod standings;
+mod tennis;
 #[macro_use]
 extern crate rust_embed;
 
@@ -63,6 +79,8 @@ fn main() {
             .value_of("root_path")
             .unwrap_or("/var/lib/scoreboard/"),
     );
+    let config = config::ScoreboardConfig::load(&root_path, |name| env::var(name).ok(), &[])
+        .expect("Could not load scoreboard.toml");
     let log_dir = root_path.join("logs");
     let _create_dir_result = fs::create_dir(&log_dir);
 
@@ -95,19 +113,30 @@ fn main() {
     let (web_response_sender, _web_response_receiver) = mpsc::channel();
     let (shell_sender, _shell_receiver) = mpsc::channel();
 
-    let settings = scoreboard_settings::ScoreboardSettings::new(settings_data, settings_path);
+    let settings = scoreboard_settings::ScoreboardSettings::new(
+        settings_data,
+        settings_path,
+        common::FileWrites::default(),
+    );
 
     // Setup ScreenProvider map
     let mut map: HashMap<ScreenId, Box<dyn ScreenProvider>> = HashMap::new();
 
     let sports: AWSScreen = AWSScreen::new(
         scheduler_sender.clone(),
-        "".to_string(),
-        "".to_string(),
+        match config.source {
+            config::SourceConfig::Api => Box::new(score_source::DemoSource),
+            _ => score_source::from_config(&config, &root_path),
+        },
+        config.refresh.clone(),
+        sport::RefreshFiles {
+            score_cache: root_path.join("demo_score_cache.json"),
+            network_usage: root_path.join("demo_network_usage.json"),
+            recap: root_path.join("demo_recap.json"),
+        },
         settings.get_settings(),
         matrix::FontBook::new(&root_path),
         matrix::PixelBook::new(&root_path),
-        matrix::MatrixMode::Demo,
     );
     map.insert(ScreenId::Smart, Box::new(sports));
 
@@ -149,12 +178,6 @@ fn main() {
     let animation = AnimationTestScreen::new(scheduler_sender.clone());
     map.insert(ScreenId::Animation, Box::new(animation));
 
-    let default = 3;
-    let slowdown: u32 = env::var("SCOREBOARD_SLOWDOWN")
-        .ok()
-        .map(|s| s.parse().unwrap_or(default))
-        .unwrap_or(default);
-
     // Message Screen
     let message_screen =
         message::MessageScreen::new(scheduler_sender.clone(), matrix::FontBook::new(&root_path));
@@ -164,13 +187,13 @@ fn main() {
     let mut rt_options = rpi_led_matrix::LedRuntimeOptions::new();
     options.set_rows(32);
     options.set_cols(64);
-    options.set_hardware_mapping("adafruit-hat-pwm");
+    options.set_hardware_mapping(&config.matrix.hardware_mapping);
     options.set_pwm_lsb_nanoseconds(50);
     options.set_refresh_rate(false);
     let _ignored_brightness_result = options.set_brightness(settings.get_brightness());
     info!("setting drop privileges to false");
     rt_options.set_drop_privileges(false);
-    rt_options.set_gpio_slowdown(slowdown);
+    rt_options.set_gpio_slowdown(config.matrix.gpio_slowdown);
     let led_matrix: rpi_led_matrix::LedMatrix =
         rpi_led_matrix::LedMatrix::new(Some(options), Some(rt_options))
             .expect("Could not setup matrix");
@@ -192,6 +215,10 @@ fn main() {
         matrix_receiver,
         map,
         settings,
+        profiles::ProfileStore::new(
+            root_path.join("demo_profiles.json"),
+            common::FileWrites::default(),
+        ),
         matrix_senders,
         None,
         matrix::MatrixMode::Demo,
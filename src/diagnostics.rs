@@ -0,0 +1,66 @@
+// Counters kept on disk so they survive reboots, reported by the webserver at /diagnostics
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const NETWORK_USAGE_PATH: &str = "network_usage.json";
+const DAYS_KEPT: usize = 14;
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct DailyUsage {
+    pub bytes: u64, // Body bytes as transferred, before decompression
+    pub fetches: u32,
+    pub not_modified: u32,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct NetworkUsage {
+    pub days: BTreeMap<String, DailyUsage>, // Keyed by UTC date, like 2021-03-14
+}
+
+impl NetworkUsage {
+    pub fn read(path: &Path) -> NetworkUsage {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|usage_string| serde_json::from_str(&usage_string).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn record(&mut self, bytes: usize, not_modified: bool) {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        let usage = self.days.entry(today).or_insert_with(DailyUsage::default);
+        usage.bytes += bytes as u64;
+        usage.fetches += 1;
+        if not_modified {
+            usage.not_modified += 1;
+        }
+        while self.days.len() > DAYS_KEPT {
+            let oldest = self.days.keys().next().unwrap().clone();
+            self.days.remove(&oldest);
+        }
+    }
+
+    pub fn write(&self, path: &Path) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|usage_string| fs::write(path, usage_string).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Failed to write network usage to {:?}: {}", path, e);
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct Diagnostics {
+    pub network_usage: NetworkUsage,
+}
+
+impl Diagnostics {
+    pub fn read(root_path: &Path) -> Diagnostics {
+        Diagnostics {
+            network_usage: NetworkUsage::read(&root_path.join(NETWORK_USAGE_PATH)),
+        }
+    }
+}
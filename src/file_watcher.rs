@@ -0,0 +1,139 @@
+use crate::common::{
+    self, CommandSource, CustomMessage, FileWrites, MatrixCommand, ScoreboardSettingsData,
+};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread::sleep;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+struct WatchedFile {
+    path: PathBuf,
+    last_contents: Option<String>,
+}
+
+impl WatchedFile {
+    fn new(path: PathBuf) -> WatchedFile {
+        let last_contents = fs::read_to_string(&path).ok();
+        WatchedFile {
+            path,
+            last_contents,
+        }
+    }
+
+    // Returns the new contents if the file changed since the last poll and the change did not
+    // come from the scoreboard itself
+    fn poll(&mut self, file_writes: &FileWrites) -> Option<String> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        if self.last_contents.as_ref() == Some(&contents) {
+            return None;
+        }
+        self.last_contents = Some(contents.clone());
+        if file_writes.is_own_write(&self.path, &contents) {
+            return None;
+        }
+        Some(contents)
+    }
+}
+
+// Watches scoreboard_settings.json and custom_message.json for edits made outside the
+// scoreboard, e.g. over SSH, and feeds valid changes back to the matrix
+pub struct FileWatcher {
+    matrix_sender: mpsc::Sender<MatrixCommand>,
+    file_writes: FileWrites,
+    settings_file: WatchedFile,
+    message_file: WatchedFile,
+}
+
+impl FileWatcher {
+    pub fn new(
+        root_path: &std::path::Path,
+        settings_path: PathBuf,
+        file_writes: FileWrites,
+        matrix_sender: mpsc::Sender<MatrixCommand>,
+    ) -> FileWatcher {
+        FileWatcher {
+            matrix_sender,
+            file_writes,
+            settings_file: WatchedFile::new(settings_path),
+            message_file: WatchedFile::new(root_path.join(common::MESSAGE_PATH)),
+        }
+    }
+
+    pub fn run(&mut self) {
+        info!(
+            "Watching {:?} and {:?} for changes",
+            self.settings_file.path, self.message_file.path
+        );
+        loop {
+            sleep(POLL_INTERVAL);
+            if let Some(contents) = self.settings_file.poll(&self.file_writes) {
+                self.reload_settings(&contents);
+            }
+            if let Some(contents) = self.message_file.poll(&self.file_writes) {
+                self.reload_custom_message(&contents);
+            }
+        }
+    }
+
+    fn reload_settings(&self, contents: &str) {
+        let settings = serde_json::from_str::<ScoreboardSettingsData>(contents)
+            .map_err(|e| e.to_string())
+            .and_then(|settings| settings.validate().map(|_| settings));
+        match settings {
+            Ok(settings) => self
+                .matrix_sender
+                .send(MatrixCommand::UpdateSettings {
+                    source: CommandSource::File(),
+                    settings,
+                })
+                .unwrap(),
+            Err(e) => error!(
+                "Ignoring change to {:?}, could not load settings: {}",
+                self.settings_file.path, e
+            ),
+        }
+    }
+
+    fn reload_custom_message(&self, contents: &str) {
+        match serde_json::from_str::<CustomMessage>(contents) {
+            Ok(message) => self
+                .matrix_sender
+                .send(MatrixCommand::SetCustomMessage {
+                    source: CommandSource::File(),
+                    message,
+                })
+                .unwrap(),
+            Err(e) => error!(
+                "Ignoring change to {:?}, could not load custom message: {}",
+                self.message_file.path, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_own_writes_ignored() {
+        let path = std::env::temp_dir().join("scoreboard_test_watched.json");
+        let file_writes = FileWrites::default();
+        file_writes.write(&path, "{}").unwrap();
+        let mut watched_file = WatchedFile::new(path.clone());
+        assert_eq!(watched_file.poll(&file_writes), None);
+
+        file_writes.write(&path, "{\"a\": 1}").unwrap();
+        assert_eq!(watched_file.poll(&file_writes), None);
+        fs::write(&path, "{\"a\": 2}").unwrap();
+        assert_eq!(
+            watched_file.poll(&file_writes),
+            Some("{\"a\": 2}".to_string())
+        );
+        assert!(!path.with_extension("tmp").exists());
+        let _ = fs::remove_file(path);
+    }
+}
@@ -7,12 +7,58 @@ use serde::Deserialize;
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 use chrono_tz::Tz;
 
+const TIMEOUTS_PER_HALF: u8 = 3;
+const CHALLENGES_PER_GAME: u8 = 2; // A third is only earned by winning both
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct FootballData {
     pub time_remaining: String,
     pub ball_position: String,
     pub down_string: String,
-    pub home_possession: Option<bool>
+    pub home_possession: Option<bool>,
+    #[serde(default)]
+    pub away_timeouts: Option<u8>,
+    #[serde(default)]
+    pub home_timeouts: Option<u8>,
+    #[serde(default)]
+    pub away_challenges: Option<u8>,
+    #[serde(default)]
+    pub home_challenges: Option<u8>,
+    #[serde(default)]
+    pub two_minute_warning: bool,
+    #[serde(default)]
+    pub drive: Option<Drive>, // The current drive, or the last one between drives
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Drive {
+    pub plays: u8,
+    pub yards: i16,
+    pub time_of_possession: String,
+}
+
+impl FootballData {
+    fn has_details(&self) -> bool {
+        self.away_timeouts.is_some()
+            || self.home_timeouts.is_some()
+            || self.away_challenges.is_some()
+            || self.home_challenges.is_some()
+            || self.drive.is_some()
+    }
+
+    // Ball inside the defense's 20, from a position like CHI 13
+    fn is_red_zone(&self, common: &game::CommonGameData) -> bool {
+        let offense = match self.home_possession {
+            Some(true) => &common.home_team,
+            Some(false) => &common.away_team,
+            None => return false,
+        };
+        let mut position = self.ball_position.split_whitespace();
+        match (position.next(), position.next().and_then(|yard| yard.parse::<u8>().ok())) {
+            (Some(side), Some(yard)) => !side.eq_ignore_ascii_case(&offense.abbreviation) && yard <= 20,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -67,6 +113,123 @@ impl PartialEq for CollegeFootballGame {
 
 impl Eq for CollegeFootballGame {}
 
+fn draw_details(
+    common: &game::CommonGameData,
+    football_data: &FootballData,
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+) {
+    let (canvas_width, _) = canvas.canvas_size();
+    let white = common::new_color(255, 255, 255);
+    let grey = common::new_color(120, 120, 120);
+    let green = common::new_color(52, 162, 35);
+    let yellow = common::new_color(255, 255, 0);
+    let red = common::new_color(255, 0, 0);
+    let row_height = font.dimensions.height + 1;
+    // Timeouts follow the abbreviation and challenges start at the middle of the panel
+    let timeouts_x = canvas_width * 9 / 32;
+    let challenges_x = canvas_width / 2;
+
+    // Each team with its timeouts and then its challenges left as pips
+    let teams = [
+        (
+            &common.away_team,
+            common.away_score,
+            football_data.away_timeouts,
+            football_data.away_challenges,
+        ),
+        (
+            &common.home_team,
+            common.home_score,
+            football_data.home_timeouts,
+            football_data.home_challenges,
+        ),
+    ];
+    for (i, (team, score, timeouts, challenges)) in teams.iter().enumerate() {
+        let baseline = (i as i32 + 1) * row_height;
+        matrix::draw_rectangle(
+            canvas,
+            (0, baseline - font.dimensions.height),
+            (1, baseline + 1),
+            &team.primary_color,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &team.abbreviation.to_ascii_uppercase(),
+            3,
+            baseline,
+            &white,
+            0,
+            false,
+        );
+        if let Some(timeouts) = timeouts {
+            for pip in 0..TIMEOUTS_PER_HALF {
+                let x = timeouts_x + pip as i32 * 4;
+                let color = if pip < *timeouts { &yellow } else { &grey };
+                matrix::draw_rectangle(canvas, (x, baseline - 3), (x + 2, baseline - 1), color);
+            }
+        }
+        if let Some(challenges) = challenges {
+            // A won challenge can leave a team with more than the usual number
+            for pip in 0..std::cmp::max(CHALLENGES_PER_GAME, *challenges) {
+                let x = challenges_x + pip as i32 * 4;
+                let color = if pip < *challenges { &red } else { &grey };
+                matrix::draw_rectangle(canvas, (x, baseline - 3), (x + 2, baseline - 1), color);
+            }
+        }
+        let score_text = score.to_string();
+        canvas.draw_text(
+            &font.led_font,
+            &score_text,
+            canvas_width - 1 - font.get_text_dimensions(&score_text).width,
+            baseline,
+            &white,
+            0,
+            false,
+        );
+    }
+
+    if let Some(drive) = &football_data.drive {
+        let drive_text = format!("{} PLAYS {} YDS", drive.plays, drive.yards);
+        canvas.draw_text(
+            &font.led_font,
+            "DRIVE",
+            2,
+            3 * row_height + 1,
+            &green,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &drive_text,
+            2,
+            4 * row_height + 1,
+            &white,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            "TOP",
+            2,
+            5 * row_height + 1,
+            &grey,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &drive.time_of_possession,
+            4 + 3 * font.dimensions.width,
+            5 * row_height + 1,
+            &white,
+            0,
+            false,
+        );
+    }
+}
+
 fn football_draw(
     common: &game::CommonGameData,
     football_data: &Option<FootballData>,
@@ -76,6 +239,12 @@ fn football_draw(
     timezone: &Tz,
 ) {
     let font = &font_book.font4x6;
+    if let Some(data) = football_data {
+        if game::show_detail_page(common, data.has_details()) {
+            draw_details(common, data, canvas, font);
+            return;
+        }
+    }
     let (away_width, home_width) = match football_data {
         Some(data) => {
             if let Some(home_possession) = data.home_possession {
@@ -99,6 +268,7 @@ fn football_draw(
     // Draw the current period
     let white = common::new_color(255, 255, 255);
     let yellow = common::new_color(255, 255, 0);
+    let red = common::new_color(200, 30, 30);
 
     let mut draw_bottom_info = |text: &str, position: (i32, i32), color: &rpi_led_matrix::LedColor| {
         canvas.draw_text(
@@ -127,13 +297,18 @@ fn football_draw(
     if let Some(football_data) = football_data {
         // Left side
         draw_bottom_info(&common.get_ordinal_text(timezone), (left_indent, bottom_row_height), &white);
-        draw_bottom_info(&football_data.time_remaining, (left_indent, top_row_height), &white);
+        if football_data.two_minute_warning {
+            draw_bottom_info("2 MIN", (left_indent, top_row_height), &yellow);
+        } else {
+            draw_bottom_info(&football_data.time_remaining, (left_indent, top_row_height), &white);
+        }
 
         // Right side
         let down_string_dimensions = font.get_text_dimensions(&football_data.down_string);
         let ball_on_text_dimensions = font.get_text_dimensions(&football_data.ball_position);
         draw_bottom_info(&football_data.down_string, (right_indent- down_string_dimensions.width, top_row_height), &white);
-        draw_bottom_info(&football_data.ball_position, (right_indent- ball_on_text_dimensions.width, bottom_row_height), &white);
+        let ball_on_color = if common.is_active_game() && football_data.is_red_zone(common) { &red } else { &white };
+        draw_bottom_info(&football_data.ball_position, (right_indent- ball_on_text_dimensions.width, bottom_row_height), ball_on_color);
 
         // Draw possession
         if common.is_active_game() {
@@ -186,3 +361,39 @@ impl game::Sport for CollegeFootballGame {
          &self.common
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_football_details() {
+        let data = r#"{
+            "common": {
+                "home_team": {"id": "3", "display_name": "Bears", "abbreviation": "CHI", "primary_color": "0b162a", "secondary_color": "c83803"},
+                "away_team": {"id": "6", "display_name": "Cowboys", "abbreviation": "DAL", "primary_color": "002244", "secondary_color": "b0b7bc"},
+                "away_score": 10,
+                "home_score": 14,
+                "status": "ACTIVE",
+                "ordinal": "4th",
+                "start_time": "2020-08-07T22:05:00Z",
+                "sport_id": 4,
+                "id": 401220225
+            },
+            "extra_data": {
+                "time_remaining": "2:00", "ball_position": "DAL 13", "down_string": "2nd & 7", "home_possession": true,
+                "away_timeouts": 2, "home_timeouts": 0, "away_challenges": 1, "two_minute_warning": true,
+                "drive": {"plays": 9, "yards": 62, "time_of_possession": "4:31"}
+            }
+        }"#;
+        let game: FootballGame = serde_json::from_str(data).unwrap();
+        let mut football_data = game.extra_data.unwrap();
+        assert!(football_data.has_details());
+        assert_eq!((football_data.away_challenges, football_data.home_challenges), (Some(1), None));
+        assert!(football_data.is_red_zone(&game.common));
+        football_data.ball_position = "CHI 13".to_string();
+        assert!(!football_data.is_red_zone(&game.common));
+        football_data.ball_position = "50".to_string();
+        assert!(!football_data.is_red_zone(&game.common));
+    }
+}
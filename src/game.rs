@@ -1,6 +1,7 @@
 use crate::aws_screen;
 use crate::common::{self, led_color_from_string};
 use crate::matrix;
+use crate::score_source::CacheValidators;
 
 
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
@@ -16,6 +17,10 @@ pub struct Response<T> {
 #[derive(Deserialize)]
 pub struct ResponseData<T> {
     pub games: Vec<T>,
+    #[serde(default)]
+    pub standings: Vec<serde_json::Value>, // Parsed leniently by standings::parse_standings
+    #[serde(default)]
+    pub upcoming: Vec<serde_json::Value>, // Next games for teams, past the ones in `games`
 }
 
 #[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
@@ -39,14 +44,23 @@ pub struct Team {
     pub secondary_color: rpi_led_matrix::LedColor, // Text color and accent color
 }
 
-fn u32_from_string<'de, D>(deserializer: D) -> Result<u32, D::Error>
+pub fn u32_from_string<'de, D>(deserializer: D) -> Result<u32, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
+    let s: String = Deserialize::deserialize(deserializer)?;
     s.parse::<u32>().map_err(D::Error::custom)
 }
 
+pub fn optional_u32_from_string<'de, D>(deserializer: D) -> Result<Option<u32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    s.map(|s| s.parse::<u32>().map_err(D::Error::custom))
+        .transpose()
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct CommonGameData {
     pub home_team: Team,
@@ -113,19 +127,69 @@ fn datetime_from_string<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Err
 where
     D: Deserializer<'de>,
 {
-    let s: &str = Deserialize::deserialize(deserializer)?;
-    let naive_time = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%SZ")
-        .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%MZ"))
+    let s: String = Deserialize::deserialize(deserializer)?;
+    let naive_time = NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%M:%SZ")
+        .or_else(|_| NaiveDateTime::parse_from_str(&s, "%Y-%m-%dT%H:%MZ"))
         .map_err(D::Error::custom)?;
     Ok(DateTime::<Utc>::from_utc(naive_time, Utc))
 }
 
-pub fn fetch_games(base_url: &str, endpoint: &str, api_key: &str) -> ureq::Response {
+const DETAIL_PAGE_TIME_SECONDS: i64 = 6;
+
+// Active games with extra details alternate between their main screen and a details page
+pub fn show_detail_page(common: &CommonGameData, has_details: bool) -> bool {
+    common.status == GameStatus::Active
+        && has_details
+        && (Utc::now().timestamp() / DETAIL_PAGE_TIME_SECONDS) % 2 == 1
+}
+
+// Time until a start, like 2D 04:10:00 or 04:10:00
+pub fn get_countdown_text(seconds: i64) -> String {
+    let seconds = std::cmp::max(seconds, 0);
+    let days = seconds / (60 * 60 * 24);
+    let time = format!(
+        "{:02}:{:02}:{:02}",
+        (seconds / (60 * 60)) % 24,
+        (seconds / 60) % 60,
+        seconds % 60
+    );
+    match days {
+        0 => time,
+        _ => format!("{}D {}", days, time),
+    }
+}
+
+pub const FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+// A compressed GET that only returns a body if it changed since `validators` were recorded
+pub fn conditional_get(url: &str, validators: &CacheValidators) -> ureq::Request {
+    let mut request = ureq::get(url);
+    request
+        .set("Accept-Encoding", "gzip, deflate")
+        .timeout(FETCH_TIMEOUT);
+    if let Some(etag) = &validators.etag {
+        request.set("If-None-Match", etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request.set("If-Modified-Since", last_modified);
+    }
+    request
+}
+
+pub fn fetch_games(
+    base_url: &str,
+    endpoint: &str,
+    api_key: &str,
+    validators: &CacheValidators,
+) -> ureq::Response {
     let url = format!("{}{}", base_url, endpoint);
-    ureq::get(&url).set("X-API-KEY", api_key).call()
+    conditional_get(&url, validators)
+        .set("X-API-KEY", api_key)
+        .call()
 }
 
-fn draw_team_box(
+// One team's row of the scoreboard, returns its height
+pub fn draw_team_box(
     canvas: &mut rpi_led_matrix::LedCanvas,
     font: &matrix::Font,
     team: &Team,
@@ -199,6 +263,13 @@ pub fn draw_scoreboard(
     );
 }
 
+// Sport specific game state that score events are derived from
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Situation {
+    pub powerplay_team: Option<u32>,
+    pub runner_in_scoring_position: bool,
+}
+
 pub trait Sport: aws_screen::AWSScreenType {
     fn get_common(&self) -> &CommonGameData;
 
@@ -216,7 +287,18 @@ pub trait Sport: aws_screen::AWSScreenType {
         let common = self.get_common();
         common.sport_id
     }
+
+    // Matches the game across refreshes, since game ids are only unique within a sport
+    fn get_game_key(&self) -> (u16, usize) {
+        let common = self.get_common();
+        (common.sport_id as u16, common.id)
+    }
+
+    fn get_situation(&self) -> Situation {
+        Situation::default()
+    }
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,6 +329,13 @@ mod tests {
         assert_eq!(team.secondary_color.blue, 90);
     }
 
+    #[test]
+    fn test_countdown_text() {
+        assert_eq!(get_countdown_text(59), "00:00:59");
+        assert_eq!(get_countdown_text(60 * 60 * 26 + 61), "1D 02:01:01");
+        assert_eq!(get_countdown_text(-5), "00:00:00");
+    }
+
     #[test]
     fn test_invalid_color() {
         let data = r#"
@@ -264,6 +353,13 @@ mod tests {
         assert!(team.is_err());
     }
 
+    #[test]
+    fn test_malformed_color() {
+        assert!(common::color_from_string("abc").is_err());
+        assert!(common::color_from_string("aé€").is_err()); // Six bytes, but not six characters
+        assert_eq!(common::color_from_string("df4601").unwrap().green, 70);
+    }
+
     #[test]
     fn test_invalid_id() {
         let data = r#"
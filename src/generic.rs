@@ -0,0 +1,59 @@
+use crate::aws_screen;
+use crate::common;
+use crate::game;
+use crate::matrix;
+use chrono_tz::Tz;
+
+// A game from a sport type this version doesn't know about, drawn as a plain scoreboard
+#[derive(Debug, Clone)]
+pub struct GenericGame {
+    pub common: game::CommonGameData,
+    pub sport_id: u16, // As sent, common.sport_id may have been swapped for one we have a screen for
+}
+
+impl aws_screen::AWSScreenType for GenericGame {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        let font = &font_book.font4x6;
+        game::draw_scoreboard(canvas, font, &self.common, 2, (2, 2));
+
+        let white = common::new_color(255, 255, 255);
+        let yellow = common::new_color(255, 255, 0);
+        canvas.draw_text(
+            &font.led_font,
+            &self.common.get_ordinal_text(timezone),
+            5,
+            23 + font.dimensions.height,
+            &white,
+            0,
+            false,
+        );
+        if self.common.status == game::GameStatus::End {
+            canvas.draw_text(
+                &font.led_font,
+                "FINAL",
+                36 + font.dimensions.width,
+                23 + font.dimensions.height,
+                &yellow,
+                0,
+                false,
+            );
+        }
+    }
+}
+
+impl game::Sport for GenericGame {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+
+    // Two unknown sports can both use the same game id
+    fn get_game_key(&self) -> (u16, usize) {
+        (self.sport_id, self.common.id)
+    }
+}
@@ -3,15 +3,23 @@ use crate::common;
 use crate::game;
 use crate::matrix;
 
+use chrono::Utc;
 use chrono_tz::Tz;
 use serde::Deserialize;
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Player {
+    #[serde(default, deserialize_with = "game::optional_u32_from_string")]
+    id: Option<u32>, // Used as the team id for favorites
     display_name: String,
-    // position: u32,
-    score: String,
+    #[serde(default)]
+    position: Option<u32>,
+    score: String, // To par for the tournament, like -12 or E
+    #[serde(default)]
+    today: Option<String>, // To par for the current round
+    #[serde(default)]
+    thru: Option<String>, // Holes completed, like 12 or F
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -19,6 +27,8 @@ pub struct Golf {
     pub common: game::CommonGameData,
     pub name: String,
     pub players: Vec<Player>,
+    #[serde(default)]
+    pub cut_line: Option<u32>, // Players placed past this position are below the cut
 }
 
 impl Ord for Golf {
@@ -41,55 +51,191 @@ impl PartialEq for Golf {
 
 impl Eq for Golf {}
 
-fn draw_player(
-    player: &Player,
-    y_offset: &mut i32,
-    canvas: &mut rpi_led_matrix::LedCanvas,
-    font: &matrix::Font,
-    name_color: &rpi_led_matrix::LedColor,
-    score_color: &rpi_led_matrix::LedColor,
-) {
-    let baseline = *y_offset + font.dimensions.height;
-    let score_width = font.get_text_dimensions(&player.score).width;
-
-    canvas.draw_text(
-        &font.led_font,
-        &player.score,
-        64 - score_width,
-        baseline,
-        score_color,
-        0,
-        false,
-    );
-    canvas.draw_text(
-        &font.led_font,
-        &player.display_name.to_ascii_uppercase(),
-        1,
-        baseline,
-        name_color,
-        0,
-        false,
-    );
-
-    *y_offset = *y_offset + font.dimensions.height + 1;
+const LEADERBOARD_SIZE: usize = 20;
+const ROWS_PER_PAGE: usize = 4;
+const PAGE_TIME_SECONDS: i64 = 5;
+const COLUMN_TIME_SECONDS: i64 = 3; // The middle column alternates between today and thru
+
+struct Row<'a> {
+    player: &'a Player,
+    pinned: bool, // A favorite outside the page, kept on the bottom row
 }
-impl game::Sport for Golf {
-    fn get_common(&self) -> &game::CommonGameData {
-        &self.common
+
+impl Golf {
+    // Like 3, or T3 when players are tied
+    fn get_position_text(&self, player: &Player) -> String {
+        match player.position {
+            Some(position) => {
+                let tied = self
+                    .players
+                    .iter()
+                    .filter(|other| other.position == Some(position))
+                    .count()
+                    > 1;
+                format!("{}{}", if tied { "T" } else { "" }, position)
+            }
+            None => String::new(),
+        }
     }
 
-    fn involves_team(&self, _target_team: u32) -> bool {
-        true
+    fn get_page_count(&self) -> usize {
+        let leaders = std::cmp::min(self.players.len(), LEADERBOARD_SIZE);
+        std::cmp::max((leaders + ROWS_PER_PAGE - 1) / ROWS_PER_PAGE, 1)
     }
-}
 
-impl aws_screen::AWSScreenType for Golf {
-    fn draw_screen(
+    // One page of the top of the leaderboard. The best placed favorite that isn't on the page
+    // takes the bottom row
+    fn get_rows(&self, page: usize, favorites: &[u32]) -> Vec<Row<'_>> {
+        let leaders = &self.players[..std::cmp::min(self.players.len(), LEADERBOARD_SIZE)];
+        let mut rows: Vec<Row> = leaders
+            .iter()
+            .skip(page * ROWS_PER_PAGE)
+            .take(ROWS_PER_PAGE)
+            .map(|player| Row {
+                player,
+                pinned: false,
+            })
+            .collect();
+        let is_favorite = |player: &Player| player.id.map_or(false, |id| favorites.contains(&id));
+        let pinned = self.players.iter().find(|player| {
+            is_favorite(player) && !rows.iter().any(|row| std::ptr::eq(row.player, *player))
+        });
+        if let Some(player) = pinned {
+            rows.truncate(ROWS_PER_PAGE - 1);
+            rows.push(Row {
+                player,
+                pinned: true,
+            });
+        }
+        rows
+    }
+
+    fn is_below_cut(&self, player: &Player) -> bool {
+        match (self.cut_line, player.position) {
+            (Some(cut_line), Some(position)) => position > cut_line,
+            _ => false,
+        }
+    }
+
+    fn draw_row(
+        &self,
+        row: &Row,
+        y_offset: i32,
+        show_today: bool,
+        favorites: &[u32],
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font: &matrix::Font,
+    ) {
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(120, 120, 120);
+        let green = common::new_color(52, 162, 35);
+        let yellow = common::new_color(255, 255, 0);
+        let baseline = y_offset + font.dimensions.height;
+        let player = row.player;
+        let is_favorite = player.id.map_or(false, |id| favorites.contains(&id));
+
+        let position = self.get_position_text(player);
+        canvas.draw_text(
+            &font.led_font,
+            &position,
+            12 - font.get_text_dimensions(&position).width,
+            baseline,
+            &grey,
+            0,
+            false,
+        );
+
+        canvas.draw_text(
+            &font.led_font,
+            &player.score,
+            width - font.get_text_dimensions(&player.score).width,
+            baseline,
+            if self.is_below_cut(player) {
+                &grey
+            } else {
+                &green
+            },
+            0,
+            false,
+        );
+
+        let column = match show_today {
+            true => player.today.as_ref(),
+            false => player.thru.as_ref(),
+        };
+        let column_start = match column {
+            Some(column) => {
+                let column_start = width - 14 - font.get_text_dimensions(column).width;
+                canvas.draw_text(
+                    &font.led_font,
+                    column,
+                    column_start,
+                    baseline,
+                    &white,
+                    0,
+                    false,
+                );
+                column_start
+            }
+            None => width - 14,
+        };
+
+        // Name, cut short to fit in front of the columns
+        let max_characters = std::cmp::max((column_start - 15) / font.dimensions.width, 0) as usize;
+        let name: String = player
+            .display_name
+            .to_ascii_uppercase()
+            .chars()
+            .take(max_characters)
+            .collect();
+        canvas.draw_text(
+            &font.led_font,
+            &name,
+            14,
+            baseline,
+            if is_favorite || row.pinned {
+                &yellow
+            } else {
+                &white
+            },
+            0,
+            false,
+        );
+    }
+
+    // Pages through the top of the leaderboard, with favorites pinned to the bottom row
+    fn draw_rows(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font: &matrix::Font,
+        favorites: &[u32],
+    ) {
+        let (width, _height) = canvas.canvas_size();
+        let red = common::new_color(200, 30, 30);
+        let now = Utc::now().timestamp();
+        let page = (now / PAGE_TIME_SECONDS) as usize % self.get_page_count();
+        let show_today = (now / COLUMN_TIME_SECONDS) % 2 == 0;
+        let rows = self.get_rows(page, favorites);
+        for (i, row) in rows.iter().enumerate() {
+            let y_offset = font.dimensions.height + 3 + i as i32 * (font.dimensions.height + 1);
+            // Dashed cut line in the gap above the first player below the cut
+            let previous_made_cut = i > 0 && !self.is_below_cut(rows[i - 1].player);
+            if previous_made_cut && self.is_below_cut(row.player) && !row.pinned {
+                for x in (0..width).step_by(2) {
+                    canvas.set(x, y_offset, &red);
+                }
+            }
+            self.draw_row(row, y_offset, show_today, favorites, canvas, font);
+        }
+    }
+
+    pub fn draw_leaderboard(
         &self,
         canvas: &mut rpi_led_matrix::LedCanvas,
         font_book: &matrix::FontBook,
-        _pixels_book: &matrix::PixelBook,
         timezone: &Tz,
+        favorites: &[u32],
     ) {
         let font = &font_book.font4x6; // Use the smallest font to fit the most info
         let green = common::new_color(52, 162, 35);
@@ -127,12 +273,98 @@ impl aws_screen::AWSScreenType for Golf {
                 );
             }
             game::GameStatus::Intermission | game::GameStatus::End | game::GameStatus::Active => {
-                let num_players = 4;
-                let mut player_offset = font.dimensions.height + 3;
-                self.players.iter().take(num_players).for_each(|player| {
-                    draw_player(player, &mut player_offset, canvas, font, &white, &green);
-                });
+                self.draw_rows(canvas, font, favorites);
             }
         }
     }
 }
+
+impl game::Sport for Golf {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+
+    // Payloads from before player ids can't be matched, so every favorite counts like it used to
+    fn involves_team(&self, target_team: u32) -> bool {
+        self.players.iter().all(|player| player.id.is_none())
+            || self
+                .players
+                .iter()
+                .any(|player| player.id == Some(target_team))
+    }
+}
+
+impl aws_screen::AWSScreenType for Golf {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        // Favorites aren't known here, the smart screen calls draw_leaderboard with them
+        self.draw_leaderboard(canvas, font_book, timezone, &[]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Sport;
+
+    #[test]
+    fn test_golf() {
+        let players: Vec<String> = (1..=8)
+            .map(|i| {
+                format!(
+                    r#"{{"id": "{}", "display_name": "Player{}", "position": {}, "score": "-{}", "today": "-1", "thru": "F"}}"#,
+                    i,
+                    i,
+                    if i == 3 { 2 } else { i },
+                    10 - i
+                )
+            })
+            .collect();
+        let data = format!(
+            r#"{{
+            "common": {{
+                "sport_id": 6,
+                "home_team": {{"id": "0", "display_name": "", "abbreviation": "", "primary_color": "000000", "secondary_color": "000000"}},
+                "away_team": {{"id": "0", "display_name": "", "abbreviation": "", "primary_color": "000000", "secondary_color": "000000"}},
+                "home_score": 0,
+                "away_score": 0,
+                "status": "ACTIVE",
+                "ordinal": "2nd",
+                "start_time": "2023-08-06T11:45Z",
+                "id": 401465542
+            }},
+            "name": "THE MASTERS",
+            "cut_line": 4,
+            "players": [{}, {{"display_name": "OLD PAYLOAD", "score": "E"}}]
+        }}"#,
+            players.join(",")
+        );
+        let golf: Golf = serde_json::from_str(&data).unwrap();
+        assert!(golf.involves_team(8));
+        assert!(!golf.involves_team(9));
+        let old_payload = Golf {
+            players: golf.players[8..].to_vec(),
+            ..golf.clone()
+        };
+        assert!(old_payload.involves_team(9));
+
+        assert_eq!(golf.get_position_text(&golf.players[0]), "1");
+        assert_eq!(golf.get_position_text(&golf.players[1]), "T2");
+        assert_eq!(golf.get_position_text(&golf.players[8]), "");
+        assert!(golf.is_below_cut(&golf.players[4]));
+        assert!(!golf.is_below_cut(&golf.players[8]));
+        assert_eq!(golf.get_page_count(), 3);
+
+        let rows = golf.get_rows(0, &[7]);
+        assert_eq!(rows.len(), 4);
+        assert!(rows[3].pinned);
+        assert_eq!(rows[3].player.id, Some(7));
+        let rows = golf.get_rows(1, &[7]);
+        assert!(rows.iter().all(|row| !row.pinned));
+    }
+}
@@ -14,6 +14,33 @@ pub struct HockeyGame {
     home_powerplay: bool,
     away_players: u8,
     home_players: u8,
+    #[serde(default)]
+    away_shots: Option<u16>, // Shots on goal
+    #[serde(default)]
+    home_shots: Option<u16>,
+    #[serde(default)]
+    clock: Option<String>, // Time left in the period
+    #[serde(default)]
+    penalties: Vec<Penalty>,
+    #[serde(default)]
+    away_empty_net: bool,
+    #[serde(default)]
+    home_empty_net: bool,
+    #[serde(default)]
+    shootout: Option<Shootout>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Penalty {
+    #[serde(deserialize_with = "game::u32_from_string")]
+    team_id: u32, // The penalized team
+    time_remaining: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Shootout {
+    away: Vec<bool>, // Each attempt in order, true when it scored
+    home: Vec<bool>,
 }
 
 impl Ord for HockeyGame {
@@ -40,7 +67,176 @@ impl game::Sport for HockeyGame{
     fn get_common(&self) -> &game::CommonGameData {
          &self.common
     }
+
+    fn get_situation(&self) -> game::Situation {
+        let powerplay_team = if self.away_powerplay {
+            Some(self.common.away_team.id)
+        } else if self.home_powerplay {
+            Some(self.common.home_team.id)
+        } else {
+            None
+        };
+        game::Situation {
+            powerplay_team,
+            ..Default::default()
+        }
+    }
 }
+impl HockeyGame {
+    fn has_details(&self) -> bool {
+        self.away_shots.is_some()
+            || self.home_shots.is_some()
+            || self.clock.is_some()
+            || !self.penalties.is_empty()
+            || self.away_empty_net
+            || self.home_empty_net
+            || self.shootout.is_some()
+    }
+
+    // Like 2ND 12:34
+    fn get_clock_text(&self) -> String {
+        let ordinal = self.common.ordinal.to_ascii_uppercase();
+        match &self.clock {
+            Some(clock) => format!("{} {}", ordinal, clock),
+            None => ordinal,
+        }
+    }
+
+    fn get_shots_text(&self) -> Option<String> {
+        match (self.away_shots, self.home_shots) {
+            (Some(away_shots), Some(home_shots)) => {
+                Some(format!("SOG {}-{}", away_shots, home_shots))
+            }
+            _ => None,
+        }
+    }
+
+    fn get_team(&self, team_id: u32) -> &game::Team {
+        if self.common.home_team.id == team_id {
+            &self.common.home_team
+        } else {
+            &self.common.away_team
+        }
+    }
+
+    // Compact team boxes with the period clock, shots and penalty clocks underneath, or the
+    // shootout attempts once a game gets there
+    fn draw_details(&self, canvas: &mut rpi_led_matrix::LedCanvas, font: &matrix::Font) {
+        let (canvas_width, _) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(120, 120, 120);
+        let green = common::new_color(52, 162, 35);
+        let red = common::new_color(200, 30, 30);
+        let yellow = common::new_color(255, 255, 0);
+
+        let teams = [
+            (
+                &self.common.away_team,
+                self.common.away_score,
+                self.away_empty_net,
+            ),
+            (
+                &self.common.home_team,
+                self.common.home_score,
+                self.home_empty_net,
+            ),
+        ];
+        let mut y_offset = 0;
+        for (team, score, empty_net) in teams.iter() {
+            let box_height = game::draw_team_box(canvas, font, team, *score, y_offset, 1, 2);
+            if *empty_net {
+                // Covers the end of long names, just left of the score
+                let score_width = font.get_text_dimensions(&score.to_string()).width;
+                let x = canvas_width - 3 - score_width - 11;
+                matrix::draw_rectangle(
+                    canvas,
+                    (x - 1, y_offset),
+                    (x + 9, y_offset + box_height),
+                    &team.primary_color,
+                );
+                canvas.draw_text(
+                    &font.led_font,
+                    "EN",
+                    x,
+                    y_offset + 1 + font.dimensions.height,
+                    &yellow,
+                    0,
+                    false,
+                );
+            }
+            y_offset += box_height;
+        }
+
+        let row_height = font.dimensions.height + 2;
+        let baselines = [y_offset + row_height, y_offset + 2 * row_height];
+        if let Some(shootout) = &self.shootout {
+            let attempts = [
+                (&self.common.away_team, &shootout.away),
+                (&self.common.home_team, &shootout.home),
+            ];
+            for ((team, attempts), baseline) in attempts.iter().zip(baselines.iter()) {
+                canvas.draw_text(
+                    &font.led_font,
+                    &team.abbreviation.to_ascii_uppercase(),
+                    2,
+                    *baseline,
+                    &white,
+                    0,
+                    false,
+                );
+                for (i, scored) in attempts.iter().enumerate() {
+                    let x = 18 + i as i32 * 4;
+                    let color = if *scored { &green } else { &red };
+                    matrix::draw_rectangle(canvas, (x, baseline - 3), (x + 2, *baseline), color);
+                }
+            }
+            return;
+        }
+
+        canvas.draw_text(
+            &font.led_font,
+            &self.get_clock_text(),
+            2,
+            baselines[0],
+            &white,
+            0,
+            false,
+        );
+        if let Some(shots_text) = self.get_shots_text() {
+            canvas.draw_text(
+                &font.led_font,
+                &shots_text,
+                2,
+                baselines[1],
+                &grey,
+                0,
+                false,
+            );
+        }
+        // Each running penalty clock in the penalized team's colors
+        for (penalty, baseline) in self.penalties.iter().zip(baselines.iter()) {
+            let team = self.get_team(penalty.team_id);
+            let text_width = font.get_text_dimensions(&penalty.time_remaining).width;
+            let x = canvas_width - 2 - text_width;
+            matrix::draw_rectangle(
+                canvas,
+                (x - 1, baseline - font.dimensions.height - 1),
+                (x + text_width, baseline + 1),
+                &team.primary_color,
+            );
+            canvas.draw_text(
+                &font.led_font,
+                &penalty.time_remaining,
+                x,
+                *baseline,
+                &team.secondary_color,
+                0,
+                false,
+            );
+        }
+    }
+}
+
 impl aws_screen::AWSScreenType for HockeyGame {
     fn draw_screen(
         &self,
@@ -49,6 +245,10 @@ impl aws_screen::AWSScreenType for HockeyGame {
         _pixels_book: &matrix::PixelBook,
         timezone: &Tz,
     ) {
+        if game::show_detail_page(&self.common, self.has_details()) {
+            self.draw_details(canvas, &font_book.font4x6);
+            return;
+        }
         let font = &font_book.font5x8;
         game::draw_scoreboard(canvas, font, &self.common, 2, (2, 2));
 
@@ -167,4 +367,37 @@ mod tests {
         assert_eq!(game.away_players, 0);
         assert_eq!(game.home_players, 0);
     }
+
+    #[test]
+    fn test_hockey_details() {
+        let data = r#"{
+            "common": {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 2,
+                "home_score": 1,
+                "status": "ACTIVE",
+                "ordinal": "3rd",
+                "start_time": "2020-08-09T19:00:00Z",
+                "sport_id": 0,
+                "id": 2019030016
+            },
+            "away_powerplay": true,
+            "home_powerplay": false,
+            "away_players": 5,
+            "home_players": 4,
+            "away_shots": 23,
+            "home_shots": 31,
+            "clock": "1:12",
+            "penalties": [{"team_id": "19", "time_remaining": "1:45"}],
+            "home_empty_net": true
+        }"#;
+
+        let game: HockeyGame = serde_json::from_str(data).unwrap();
+        assert!(game.has_details());
+        assert_eq!(game.get_clock_text(), "3RD 1:12");
+        assert_eq!(game.get_shots_text().unwrap(), "SOG 23-31");
+        assert_eq!(game.get_team(game.penalties[0].team_id).abbreviation, "STL");
+        assert!(game.shootout.is_none());
+    }
 }
@@ -4,23 +4,40 @@ mod animation;
 mod aws_screen;
 mod baseball;
 mod basketball;
+mod bundle;
+mod celebration;
 mod button;
 mod clock;
 mod common;
+mod config;
+mod countdown;
+mod cricket;
 mod custom_message;
+mod diagnostics;
+mod file_watcher;
 mod flappy;
 mod football;
 mod game;
+mod generic;
 mod golf;
 mod hockey;
 mod matrix;
 mod message;
+mod motorsport;
 mod patch_notes;
+mod profiles;
+mod recap;
 mod scheduler;
+mod score_events;
+mod score_source;
 mod scoreboard_settings;
 mod setup_screen;
 mod shell_executor;
+mod soccer;
+mod split_view;
 mod sport;
+mod standings;
+mod tennis;
 mod updater;
 mod webserver;
 #[macro_use]
@@ -41,7 +58,6 @@ use std::sync::{mpsc, Arc};
 use std::thread::sleep;
 use std::time::Duration;
 use updater::Updater;
-const V2_URL: &str = "https://uhoijpn7d1.execute-api.us-east-2.amazonaws.com/Prod/";
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let matches = clap::App::new("Schmidt Scoreboard")
@@ -66,6 +82,39 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .value_name("wait")
             .help("Wait for 90 seconds before doing anything important, useful to give DHCP time to unfuck itself")
             .takes_value(false))
+        .arg(clap::Arg::with_name("set")
+            .long("set")
+            .value_name("key=value")
+            .help("Override a scoreboard.toml value, like --set webserver.port=8080")
+            .takes_value(true)
+            .multiple(true)
+            .number_of_values(1))
+        .arg(clap::Arg::with_name("print_config")
+            .long("print-config")
+            .help("Print the effective configuration and exit")
+            .takes_value(false))
+        .subcommand(clap::SubCommand::with_name("export")
+            .about("Export settings, custom message, and profiles to a configuration bundle")
+            .arg(clap::Arg::with_name("output")
+                .help("File to write the bundle to")
+                .required(true)
+                .index(1))
+            .arg(clap::Arg::with_name("include_secrets")
+                .short("s")
+                .long("include_secrets")
+                .help("Also export secrets.txt with the API key")
+                .takes_value(false)))
+        .subcommand(clap::SubCommand::with_name("import")
+            .about("Import a configuration bundle into root_path, the scoreboard should not be running")
+            .arg(clap::Arg::with_name("input")
+                .help("Bundle file to import")
+                .required(true)
+                .index(1))
+            .arg(clap::Arg::with_name("dry_run")
+                .short("n")
+                .long("dry_run")
+                .help("Print what would change without writing anything")
+                .takes_value(false)))
         .get_matches();
 
     let root_path = PathBuf::from(
@@ -73,6 +122,55 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .value_of("root_path")
             .unwrap_or("/var/lib/scoreboard/"),
     );
+
+    let mut overrides: Vec<(String, String)> = Vec::new();
+    if matches.is_present("wait") {
+        overrides.push(("startup.wait".to_string(), "true".to_string()));
+    }
+    if matches.is_present("skip_update") {
+        overrides.push(("startup.skip_update".to_string(), "true".to_string()));
+    }
+    for set_value in matches.values_of("set").into_iter().flatten() {
+        match set_value.find('=') {
+            Some(index) => overrides.push((
+                set_value[..index].trim().to_string(),
+                set_value[index + 1..].trim().to_string(),
+            )),
+            None => return Err(format!("Expected key=value, got {}", set_value).into()),
+        }
+    }
+    let config =
+        config::ScoreboardConfig::load(&root_path, |name| env::var(name).ok(), &overrides)?;
+    if matches.is_present("print_config") {
+        print!("{}", config.to_printable_string());
+        return Ok(());
+    }
+
+    if let Some(export_matches) = matches.subcommand_matches("export") {
+        let bundle =
+            bundle::export_bundle(&root_path, export_matches.is_present("include_secrets"))?;
+        fs::write(
+            export_matches.value_of("output").unwrap(),
+            serde_json::to_string_pretty(&bundle)?,
+        )?;
+        println!("Exported configuration from {:?}", root_path);
+        return Ok(());
+    }
+    if let Some(import_matches) = matches.subcommand_matches("import") {
+        let bundle_string = fs::read_to_string(import_matches.value_of("input").unwrap())?;
+        let bundle: bundle::ConfigBundle = serde_json::from_str(&bundle_string)?;
+        let report =
+            bundle::import_offline(&bundle, &root_path, import_matches.is_present("dry_run"))?;
+        if report.changes.is_empty() {
+            println!("No changes");
+        }
+        report
+            .changes
+            .iter()
+            .for_each(|change| println!("{}", change));
+        return Ok(());
+    }
+
     let log_dir = root_path.join("logs");
     let _create_dir_result = fs::create_dir(&log_dir);
 
@@ -90,14 +188,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .start()
         .unwrap();
 
-    if matches.is_present("wait") {
+    if config.startup.wait {
         info!("Waiting 90 seconds");
         sleep(Duration::from_secs(90));
     } else {
         info!("Starting up now");
     }
 
-    let skip_update = matches.is_present("skip_update");
+    let skip_update = config.startup.skip_update;
     patch_notes::log_patch_notes();
 
     let mut updater: Updater;
@@ -111,17 +209,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Skipping updater service");
     }
 
-    let secrets_path = root_path.join("secrets.txt");
     let settings_path = root_path.join("scoreboard_settings.json");
-    info!("Loading secrets from {:?}", secrets_path);
     info!("Loading settings from {:?}", settings_path);
 
-    let api_key = fs::read_to_string(&secrets_path).unwrap_or_else(|_| {
-        panic!(
-            "Could not read from secrets.txt at path {:?}",
-            &secrets_path
-        )
-    });
     let settings_string = fs::read_to_string(&settings_path).unwrap_or_else(|_| {
         panic!(
             "Could not read scoreboard settings at path {:?}",
@@ -131,13 +221,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let settings_data: common::ScoreboardSettingsData = serde_json::from_str(&settings_string)
         .expect("Could not parse scoreboard settings from json");
     let settings_data = Arc::from(settings_data);
+    let watched_settings_path = settings_path.clone();
 
     let (matrix_sender, matrix_receiver) = mpsc::channel();
     let (scheduler_sender, scheduler_receiver) = mpsc::channel();
     let (web_response_sender, web_response_receiver) = mpsc::channel();
     let (shell_sender, shell_receiver) = mpsc::channel();
 
-    let mut settings = scoreboard_settings::ScoreboardSettings::new(settings_data, settings_path);
+    let file_writes = common::FileWrites::default();
+    let mut settings = scoreboard_settings::ScoreboardSettings::new(
+        settings_data,
+        settings_path,
+        file_writes.clone(),
+    );
     settings.set_version(7);
 
     if settings.get_settings().setup_state == common::SetupState::Factory {
@@ -152,7 +248,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     std::thread::spawn(move || {
         shell.run();
     });
-    let mut button_handler = button::ButtonHandler::new(matrix_sender.clone());
+    let mut button_handler = button::ButtonHandler::new(matrix_sender.clone(), config.button.pin);
     std::thread::spawn(move || {
         button_handler.run();
     });
@@ -180,19 +276,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap();
     }
 
-    let v2_url = env::var("V2_URL").unwrap_or_else(|_| V2_URL.to_string()); // TODO use the actual new AWS URL
-
     // Setup ScreenProvider map
     let mut map: HashMap<ScreenId, Box<dyn ScreenProvider>> = HashMap::new();
 
     let sports: AWSScreen = AWSScreen::new(
         scheduler_sender.clone(),
-        v2_url,
-        api_key,
+        score_source::from_config(&config, &root_path),
+        config.refresh.clone(),
+        sport::RefreshFiles {
+            score_cache: root_path.join(sport::SCORE_CACHE_PATH),
+            network_usage: root_path.join(diagnostics::NETWORK_USAGE_PATH),
+            recap: root_path.join(recap::RECAP_PATH),
+        },
         settings.get_settings(),
         matrix::FontBook::new(&root_path),
         matrix::PixelBook::new(&root_path),
-        matrix::MatrixMode::Production,
     );
     map.insert(ScreenId::Smart, Box::new(sports));
 
@@ -234,12 +332,6 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let animation = AnimationTestScreen::new(scheduler_sender.clone());
     map.insert(ScreenId::Animation, Box::new(animation));
 
-    let default = 3;
-    let slowdown: u32 = env::var("SCOREBOARD_SLOWDOWN")
-        .ok()
-        .map(|s| s.parse().unwrap_or(default))
-        .unwrap_or(default);
-
     // Message Screen
     let message_screen =
         message::MessageScreen::new(scheduler_sender.clone(), matrix::FontBook::new(&root_path));
@@ -249,13 +341,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut rt_options = rpi_led_matrix::LedRuntimeOptions::new();
     options.set_rows(32);
     options.set_cols(64);
-    options.set_hardware_mapping("adafruit-hat-pwm");
+    options.set_hardware_mapping(&config.matrix.hardware_mapping);
     options.set_pwm_lsb_nanoseconds(50);
     options.set_refresh_rate(false);
     let _ignored_brightness_result = options.set_brightness(settings.get_brightness());
     info!("setting drop privileges to false");
     rt_options.set_drop_privileges(false);
-    rt_options.set_gpio_slowdown(slowdown);
+    rt_options.set_gpio_slowdown(config.matrix.gpio_slowdown);
     let led_matrix: rpi_led_matrix::LedMatrix =
         rpi_led_matrix::LedMatrix::new(Some(options), Some(rt_options))
             .expect("Could not setup matrix");
@@ -265,19 +357,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         scheduler.run();
     });
 
-    let daily_reboot: bool = env::var("DAILY_REBOOT")
-        .map(|reboot_value| reboot_value.parse().unwrap_or(true))
-        .unwrap_or(true);
-    let reboot_time: u8 = std::cmp::min(
-        env::var("REBOOT_TIME")
-            .map(|reboot_time| reboot_time.parse::<u8>().unwrap_or(3))
-            .unwrap_or(3),
-        23,
-    );
-    let daily_reboot = match daily_reboot {
-        true => Some(reboot_time),
-        false => None,
-    };
+    let daily_reboot = config.get_daily_reboot();
 
     let matrix_senders = matrix::Senders {
         webserver_responder: web_response_sender,
@@ -291,14 +371,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         matrix_receiver,
         map,
         settings,
+        profiles::ProfileStore::new(
+            root_path.join(common::PROFILES_PATH),
+            file_writes.clone(),
+        ),
         matrix_senders,
         daily_reboot,
         matrix::MatrixMode::Production,
     );
 
+    let mut file_watcher = file_watcher::FileWatcher::new(
+        &root_path,
+        watched_settings_path,
+        file_writes.clone(),
+        matrix_sender.clone(),
+    );
+    std::thread::spawn(move || {
+        file_watcher.run();
+    });
+
     let webserver_sender = matrix_sender;
     std::thread::spawn(move || {
-        webserver::run_webserver(webserver_sender, web_response_receiver, root_path);
+        webserver::run_webserver(
+            webserver_sender,
+            web_response_receiver,
+            root_path,
+            file_writes,
+            config.webserver.port,
+        );
     });
     info!("Starting matrix runner");
     matrix.run();
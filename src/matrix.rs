@@ -1,8 +1,10 @@
+use crate::bundle;
 use crate::common;
 use crate::common::Pixels;
 use crate::common::ScoreboardSettingsData;
 use crate::flappy;
 use crate::message;
+use crate::profiles::{self, ProfileStore};
 use crate::scheduler;
 use crate::scoreboard_settings::ScoreboardSettings;
 use crate::setup_screen;
@@ -17,8 +19,11 @@ use std::str;
 use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
-const PRIORITY_SCREENS: [common::ScreenId; 3] = [
+const PROFILE_MESSAGE_TIME: Duration = Duration::from_secs(3);
+const PRIORITY_SCREENS: [common::ScreenId; 5] = [
     common::ScreenId::Smart,
+    common::ScreenId::Countdown,
+    common::ScreenId::Recap,
     common::ScreenId::Clock,
     common::ScreenId::CustomMessage,
 ];
@@ -33,6 +38,7 @@ pub struct Matrix<'a> {
     receiver: mpsc::Receiver<common::MatrixCommand>, // Receive commands from the button, the webserver, and responses to shell commands
     screens_map: HashMap<common::ScreenId, Box<dyn ScreenProvider + 'a>>, // The map of all the active screens
     settings: ScoreboardSettings, // The main scoreboard settings
+    profiles: ProfileStore,       // Named settings profiles that can be switched or scheduled
     webserver_responder: mpsc::Sender<common::WebserverResponse>, // Send responses to the webserver
     shell_sender: mpsc::Sender<common::ShellCommand>, // Send commands to shell
     scheduler_sender: mpsc::Sender<scheduler::DelayedCommand>,
@@ -54,6 +60,7 @@ impl<'a> Matrix<'a> {
         receiver: mpsc::Receiver<common::MatrixCommand>,
         map: HashMap<common::ScreenId, Box<dyn ScreenProvider + 'a>>,
         settings: ScoreboardSettings,
+        profiles: ProfileStore,
         senders: Senders,
         daily_reboot: Option<u8>,
         matrix_mode: MatrixMode,
@@ -63,6 +70,7 @@ impl<'a> Matrix<'a> {
             receiver,
             screens_map: map,
             settings,
+            profiles,
             webserver_responder: senders.webserver_responder,
             shell_sender: senders.shell_sender,
             scheduler_sender: senders.scheduler_sender,
@@ -138,6 +146,24 @@ impl<'a> Matrix<'a> {
         }
     }
 
+    fn get_custom_message_screen(&mut self) -> &mut custom_message::CustomMessageScreen {
+        self.get_mut_screen(&common::ScreenId::CustomMessage)
+            .as_any()
+            .downcast_mut::<custom_message::CustomMessageScreen>()
+            .expect("Could not get custom message screen")
+    }
+
+    // Save the current power state and reboot, used when a change can only be applied at startup,
+    // like brightness which is set when the matrix is created
+    fn restart_scoreboard(&mut self) {
+        self.settings
+            .set_startup_settings(Some(true), Some(*self.settings.get_auto_power()));
+        self.settings.set_power(&true);
+        self.settings.set_auto_power(&false);
+        self.show_message("Rebooting...".to_string());
+        self.send_command(common::ShellCommand::Reboot { settings: None });
+    }
+
     // Returns what the power state and target screen of the system should be after priority check
     fn check_priority(&mut self) -> Option<common::ScreenId> {
         let auto_power_mode = *self.settings.get_auto_power_mode();
@@ -152,9 +178,9 @@ impl<'a> Matrix<'a> {
             self.last_priority_check = Some(Instant::now());
             let priority_screen = PRIORITY_SCREENS.iter().find(|id| {
                 let settings = self.settings.get_settings();
-                let screen = self.get_mut_screen(id);
+                let screen = self.get_mut_screen(id.get_base_id());
                 screen.update_settings(settings);
-                let priority = screen.has_priority(&auto_power_mode);
+                let priority = screen.has_priority(id, &auto_power_mode);
                 info!("Priority of {:?}: {}", id, priority);
                 priority
             });
@@ -196,6 +222,104 @@ impl<'a> Matrix<'a> {
             .unwrap();
     }
 
+    fn schedule_profile(&mut self, name: &str) {
+        let schedule = match self.profiles.get_profile(name).and_then(|profile| profile.schedule) {
+            Some(schedule) => schedule,
+            None => return,
+        };
+        let generation = self.profiles.next_schedule_generation(name);
+        let delay = profiles::time_until_schedule(&schedule, self.settings.get_timezone());
+        info!(
+            "Scheduling profile {} for {:02}:{:02}, delaying for {:?}",
+            name, schedule.hour, schedule.minute, delay
+        );
+        self.scheduler_sender
+            .send(scheduler::DelayedCommand::new(
+                scheduler::Command::MatrixCommand(common::MatrixCommand::ScheduledProfile {
+                    name: name.to_owned(),
+                    generation,
+                }),
+                Some(delay),
+            ))
+            .unwrap();
+    }
+
+    // Apply a profile on top of the current settings, returns false if there is no such profile
+    fn activate_profile(&mut self, name: &str) -> bool {
+        let profile = match self.profiles.get_profile(name) {
+            Some(profile) => profile.clone(),
+            None => {
+                error!("Could not find profile {}", name);
+                return false;
+            }
+        };
+        info!("Activating profile {}", name);
+        let original_brightness = self.settings.get_brightness();
+        self.deactivate_screen();
+        self.settings.apply_profile(&profile.settings);
+        if let Some(custom_message) = profile.custom_message {
+            if let Some(root_path) = self.settings.file_path.parent() {
+                if let Err(e) = common::write_custom_message(
+                    root_path,
+                    &custom_message,
+                    &self.settings.file_writes,
+                ) {
+                    error!("Failed to write custom message for profile {}: {}", name, e);
+                }
+            }
+            self.get_custom_message_screen().set_message(custom_message);
+        }
+        self.profiles.set_active(Some(profile.name));
+        self.last_priority_check = None;
+
+        if self.settings.get_brightness() != original_brightness {
+            self.restart_scoreboard();
+        } else {
+            // Let the power command take care of activating the new screen and clearing the canvas
+            self.scheduler_sender
+                .send(scheduler::DelayedCommand::new(
+                    scheduler::Command::MatrixCommand(common::MatrixCommand::SetPower {
+                        source: common::CommandSource::Task(),
+                        power: Some(*self.settings.get_power()),
+                    }),
+                    None,
+                ))
+                .unwrap();
+        }
+        true
+    }
+
+    fn import_config(
+        &mut self,
+        bundle: bundle::ConfigBundle,
+        dry_run: bool,
+    ) -> Result<bundle::ImportReport, String> {
+        bundle::check_version(&bundle)?;
+        let root_path = self
+            .settings
+            .file_path
+            .parent()
+            .ok_or_else(|| "Could not find root path".to_string())?
+            .to_path_buf();
+        let current_settings = self.settings.get_settings();
+        let changes = bundle::describe_import(&bundle, &root_path, &current_settings);
+        if !dry_run {
+            info!("Importing configuration bundle: {:?}", changes);
+            bundle::write_bundle_files(&bundle, &root_path, &self.settings.file_writes)
+                .map_err(|e| e.to_string())?;
+            self.settings
+                .set_settings(bundle::merge_settings(&current_settings, &bundle.settings));
+            // Profiles, the API key and brightness are only read at startup
+            self.restart_scoreboard();
+        }
+        Ok(bundle::ImportReport {
+            dry_run,
+            scoreboard_version: bundle.scoreboard_version,
+            changes,
+            secrets_ignored: false,
+        })
+    }
+
     // This is the main loop of the entire code
     // Call this after everything else is set up
     pub fn run(&mut self) {
@@ -215,6 +339,18 @@ impl<'a> Matrix<'a> {
 
         self.settings.set_startup_settings(None, None); // clear startup settings
 
+        let scheduled_profiles: Vec<String> = self
+            .profiles
+            .get_profiles()
+            .profiles
+            .into_iter()
+            .filter(|profile| profile.schedule.is_some())
+            .map(|profile| profile.name)
+            .collect();
+        scheduled_profiles
+            .iter()
+            .for_each(|name| self.schedule_profile(name));
+
         self.activate_screen();
         loop {
             let command = self.receiver.recv_timeout(Duration::from_secs(60));
@@ -306,25 +442,26 @@ impl<'a> Matrix<'a> {
                             self.settings.get_settings(),
                         ));
                     }
-                    common::MatrixCommand::UpdateSettings(settings) => {
-                        let original_brightness = self.settings.get_brightness();
-                        self.settings.update_settings(settings);
-                        let new_brightness = self.settings.get_brightness();
-                        self.update_settings_on_active_screen();
-                        self.send_response(common::WebserverResponse::UpdateSettings(
-                            self.settings.get_settings(),
-                        ));
-                        self.last_priority_check = None;
-                        if original_brightness != new_brightness {
-                            // Restart the scoreboard
-                            self.settings.set_startup_settings(
-                                Some(true),
-                                Some(*self.settings.get_auto_power()),
-                            );
-                            self.settings.set_power(&true);
-                            self.settings.set_auto_power(&false);
-                            self.show_message("Rebooting...".to_string());
-                            self.send_command(common::ShellCommand::Reboot { settings: None });
+                    common::MatrixCommand::UpdateSettings { source, settings } => {
+                        if source == common::CommandSource::File()
+                            && settings == *self.settings.get_settings()
+                        {
+                            debug!("Settings file matches current settings, nothing to reload");
+                        } else {
+                            let original_brightness = self.settings.get_brightness();
+                            self.settings.update_settings(settings);
+                            let new_brightness = self.settings.get_brightness();
+                            self.update_settings_on_active_screen();
+                            if source == common::CommandSource::Webserver() {
+                                self.send_response(common::WebserverResponse::UpdateSettings(
+                                    self.settings.get_settings(),
+                                ));
+                            }
+                            self.last_priority_check = None;
+                            if original_brightness != new_brightness {
+                                // Restart the scoreboard
+                                self.restart_scoreboard();
+                            }
                         }
                     }
                     common::MatrixCommand::Reboot { is_nightly_reboot } => {
@@ -477,25 +614,118 @@ impl<'a> Matrix<'a> {
                         }
                     }
                     common::MatrixCommand::GetCustomMessage() => {
-                        let custom_message_screen = self
-                            .get_mut_screen(&common::ScreenId::CustomMessage)
-                            .as_any()
-                            .downcast_mut::<custom_message::CustomMessageScreen>()
-                            .expect("Could not get custom message screen");
-                        let custom_message = custom_message_screen.get_message();
+                        let custom_message = self.get_custom_message_screen().get_message();
 
                         self.send_response(common::WebserverResponse::GetCustomMessage(
                             custom_message,
                         ));
                     }
-                    common::MatrixCommand::SetCustomMessage(custom_message) => {
-                        let custom_message_screen = self
-                            .get_mut_screen(&common::ScreenId::CustomMessage)
-                            .as_any()
-                            .downcast_mut::<custom_message::CustomMessageScreen>()
-                            .expect("Could not get custom message screen");
-                        custom_message_screen.set_message(custom_message);
-                        self.send_response(common::WebserverResponse::SetCustomMessage());
+                    common::MatrixCommand::SetCustomMessage { source, message } => {
+                        if source == common::CommandSource::File()
+                            && message == self.get_custom_message_screen().get_message()
+                        {
+                            debug!("Custom message file matches current message, nothing to reload");
+                        } else {
+                            self.get_custom_message_screen().set_message(message);
+                        }
+                        if source == common::CommandSource::Webserver() {
+                            self.send_response(common::WebserverResponse::SetCustomMessage());
+                        }
+                    }
+                    common::MatrixCommand::GetProfiles() => {
+                        self.send_response(common::WebserverResponse::GetProfiles(
+                            self.profiles.get_profiles(),
+                        ));
+                    }
+                    common::MatrixCommand::CreateProfile(profile) => {
+                        if profile.name.trim().is_empty() {
+                            self.send_response(common::WebserverResponse::CreateProfile(None));
+                        } else {
+                            let name = profile.name.clone();
+                            self.profiles.save_profile(profile);
+                            self.schedule_profile(&name);
+                            self.send_response(common::WebserverResponse::CreateProfile(Some(
+                                self.profiles.get_profiles(),
+                            )));
+                        }
+                    }
+                    common::MatrixCommand::ActivateProfile { source, name } => {
+                        let activated = self.activate_profile(&name);
+                        if source == common::CommandSource::Webserver() {
+                            self.send_response(common::WebserverResponse::ActivateProfile(
+                                if activated {
+                                    Some(self.settings.get_settings())
+                                } else {
+                                    None
+                                },
+                            ));
+                        }
+                    }
+                    common::MatrixCommand::CycleProfile() => {
+                        match self.profiles.get_next_profile_name() {
+                            Some(name) => {
+                                // Flash the name so the press has visible feedback, unless the
+                                // profile triggered a reboot message
+                                if self.activate_profile(&name)
+                                    && !self.message_screen.is_message_set()
+                                {
+                                    self.show_message(name);
+                                    self.scheduler_sender
+                                        .send(scheduler::DelayedCommand::new(
+                                            scheduler::Command::MatrixCommand(
+                                                common::MatrixCommand::HideMessage(),
+                                            ),
+                                            Some(PROFILE_MESSAGE_TIME),
+                                        ))
+                                        .unwrap();
+                                }
+                            }
+                            None => {
+                                // Without profiles the medium press keeps toggling power
+                                self.scheduler_sender
+                                    .send(scheduler::DelayedCommand::new(
+                                        scheduler::Command::MatrixCommand(
+                                            common::MatrixCommand::SetPower {
+                                                source: common::CommandSource::Button(),
+                                                power: None,
+                                            },
+                                        ),
+                                        None,
+                                    ))
+                                    .unwrap();
+                            }
+                        }
+                    }
+                    common::MatrixCommand::HideMessage() => {
+                        self.hide_message();
+                        if !*self.settings.get_power() {
+                            // Nothing else will redraw the canvas while the power is off
+                            canvas.clear();
+                            canvas = self.led_matrix.swap(canvas);
+                            canvas.clear();
+                        }
+                    }
+                    common::MatrixCommand::ScheduledProfile { name, generation } => {
+                        if self.profiles.is_current_schedule(&name, generation) {
+                            self.activate_profile(&name);
+                            self.schedule_profile(&name);
+                        } else {
+                            info!("Ignoring outdated schedule for profile {}", name);
+                        }
+                    }
+                    common::MatrixCommand::ImportConfig { bundle, dry_run } => {
+                        let result = self.import_config(*bundle, dry_run);
+                        self.send_response(common::WebserverResponse::ImportConfig(result));
+                    }
+                    common::MatrixCommand::DeleteProfile(name) => {
+                        let deleted = self.profiles.delete_profile(&name);
+                        self.send_response(common::WebserverResponse::DeleteProfile(
+                            if deleted {
+                                Some(self.profiles.get_profiles())
+                            } else {
+                                None
+                            },
+                        ));
                     }
                 }
             };
@@ -805,6 +1035,29 @@ pub fn draw_text_centered_horizontally(
     );
 }
 
+const MARQUEE_STEP_MILLIS: i64 = 60;
+
+// Draws text at x if it fits before the right edge, otherwise scrolls it across the whole row.
+// The scroll position comes from the clock so screens don't have to keep any state for it.
+pub fn draw_marquee(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &Font,
+    text: &str,
+    x: i32,
+    y_baseline: i32,
+    color: &rpi_led_matrix::LedColor,
+) {
+    let (canvas_width, _canvas_height) = canvas.canvas_size();
+    let text_width = font.get_text_dimensions(text).width;
+    let x = if x + text_width <= canvas_width {
+        x
+    } else {
+        let cycle = (text_width + canvas_width) as i64;
+        canvas_width - (Utc::now().timestamp_millis() / MARQUEE_STEP_MILLIS % cycle) as i32
+    };
+    canvas.draw_text(&font.led_font, text, x, y_baseline, color, 0, false);
+}
+
 pub fn draw_lines(
     canvas: &mut rpi_led_matrix::LedCanvas,
     lines: &[&str],
@@ -904,7 +1157,8 @@ pub trait ScreenProvider {
 
     fn as_any(&mut self) -> &mut dyn Any;
 
-    fn has_priority(&mut self, _power_mode: &common::AutoPowerMode) -> bool {
+    // Screens that draw several ids, like the smart screen, are asked once per id
+    fn has_priority(&mut self, _id: &common::ScreenId, _power_mode: &common::AutoPowerMode) -> bool {
         false
     }
 }
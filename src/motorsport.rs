@@ -0,0 +1,305 @@
+use crate::aws_screen;
+use crate::common;
+use crate::game;
+use crate::matrix;
+
+use chrono::Utc;
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+
+const PAGE_TIME_SECONDS: i64 = 4;
+const DRIVERS_PER_PAGE: usize = 3;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum FlagState {
+    Green,
+    Yellow,
+    #[serde(rename = "SC")]
+    SafetyCar,
+    Red,
+    #[serde(other)]
+    Unknown, // Like a virtual safety car or the chequered flag
+}
+
+impl FlagState {
+    fn get_color(&self) -> Option<rpi_led_matrix::LedColor> {
+        match self {
+            FlagState::Green => Some(common::new_color(0, 255, 0)),
+            FlagState::Yellow => Some(common::new_color(255, 255, 0)),
+            FlagState::SafetyCar => Some(common::new_color(255, 140, 0)),
+            FlagState::Red => Some(common::new_color(255, 0, 0)),
+            FlagState::Unknown => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Driver {
+    #[serde(deserialize_with = "game::u32_from_string")]
+    pub id: u32, // Used as the team id for favorites
+    pub abbreviation: String,
+    pub position: u32,
+    pub gap: String, // Gap to the leader, like +1.234 or +1 LAP, empty for the leader
+    #[serde(deserialize_with = "common::led_color_from_string")]
+    pub team_color: rpi_led_matrix::LedColor,
+}
+
+// The common teams are placeholders, like golf, since a race is between drivers
+#[derive(Deserialize, Debug, Clone)]
+pub struct MotorsportEvent {
+    pub common: game::CommonGameData,
+    pub name: String,    // Event name, like MONACO GP
+    pub session: String, // Like RACE, QUALI or FP1
+    pub lap: Option<u32>,
+    pub total_laps: Option<u32>,
+    pub flag: Option<FlagState>,
+    pub drivers: Vec<Driver>, // In running order
+}
+
+impl Ord for MotorsportEvent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.common.cmp(&other.common)
+    }
+}
+
+impl PartialOrd for MotorsportEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MotorsportEvent {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.common,) == (&other.common,)
+    }
+}
+
+impl Eq for MotorsportEvent {}
+
+impl game::Sport for MotorsportEvent {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+
+    fn involves_team(&self, target_team: u32) -> bool {
+        self.drivers.iter().any(|driver| driver.id == target_team)
+    }
+}
+
+fn draw_border(canvas: &mut rpi_led_matrix::LedCanvas, color: &rpi_led_matrix::LedColor) {
+    let (width, height) = canvas.canvas_size();
+    canvas.draw_line(0, 0, width - 1, 0, color);
+    canvas.draw_line(0, height - 1, width - 1, height - 1, color);
+    canvas.draw_line(0, 0, 0, height - 1, color);
+    canvas.draw_line(width - 1, 0, width - 1, height - 1, color);
+}
+
+impl MotorsportEvent {
+    fn get_page_count(&self) -> usize {
+        std::cmp::max(
+            (self.drivers.len() + DRIVERS_PER_PAGE - 1) / DRIVERS_PER_PAGE,
+            1,
+        )
+    }
+
+    fn get_page(&self, page: usize) -> &[Driver] {
+        let start = std::cmp::min(page * DRIVERS_PER_PAGE, self.drivers.len());
+        let end = std::cmp::min(start + DRIVERS_PER_PAGE, self.drivers.len());
+        &self.drivers[start..end]
+    }
+
+    fn draw_running_order(&self, canvas: &mut rpi_led_matrix::LedCanvas, font: &matrix::Font) {
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(150, 150, 150);
+        let page = (Utc::now().timestamp() / PAGE_TIME_SECONDS) as usize % self.get_page_count();
+        for (i, driver) in self.get_page(page).iter().enumerate() {
+            let y_offset = 10 + i as i32 * (font.dimensions.height + 1);
+            let baseline = y_offset + font.dimensions.height;
+            canvas.draw_text(
+                &font.led_font,
+                &driver.position.to_string(),
+                2,
+                baseline,
+                &grey,
+                0,
+                false,
+            );
+            matrix::draw_rectangle(
+                canvas,
+                (12, y_offset),
+                (14, y_offset + font.dimensions.height),
+                &driver.team_color,
+            );
+            canvas.draw_text(
+                &font.led_font,
+                &driver.abbreviation.to_ascii_uppercase(),
+                16,
+                baseline,
+                &white,
+                0,
+                false,
+            );
+            canvas.draw_text(
+                &font.led_font,
+                &driver.gap,
+                width - font.get_text_dimensions(&driver.gap).width - 2,
+                baseline,
+                &grey,
+                0,
+                false,
+            );
+        }
+    }
+}
+
+impl aws_screen::AWSScreenType for MotorsportEvent {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        let font = &font_book.font4x6;
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let yellow = common::new_color(255, 255, 0);
+
+        if self.common.is_active_game() {
+            if let Some(color) = self.flag.and_then(|flag| flag.get_color()) {
+                draw_border(canvas, &color);
+            }
+        }
+
+        match self.common.status {
+            game::GameStatus::Pregame => {
+                let name = self.name.to_ascii_uppercase();
+                canvas.draw_text(
+                    &font.led_font,
+                    &name,
+                    (width - font.get_text_dimensions(&name).width) / 2,
+                    2 + font.dimensions.height,
+                    &white,
+                    0,
+                    false,
+                );
+                let seconds = (self.common.start_time - Utc::now()).num_seconds();
+                let (label, text) = match seconds > 60 * 60 * 24 * 7 {
+                    // Too far out for a countdown to mean much
+                    true => ("", self.common.get_ordinal_text(timezone)),
+                    false => ("LIGHTS OUT", game::get_countdown_text(seconds)),
+                };
+                canvas.draw_text(
+                    &font.led_font,
+                    label,
+                    (width - font.get_text_dimensions(label).width) / 2,
+                    12 + font.dimensions.height,
+                    &yellow,
+                    0,
+                    false,
+                );
+                canvas.draw_text(
+                    &font.led_font,
+                    &text,
+                    (width - font.get_text_dimensions(&text).width) / 2,
+                    21 + font.dimensions.height,
+                    &white,
+                    0,
+                    false,
+                );
+            }
+            _ => {
+                let session_text = match self.common.status {
+                    game::GameStatus::End => format!("{} FINAL", self.session),
+                    _ => self.session.clone(),
+                };
+                canvas.draw_text(
+                    &font.led_font,
+                    &session_text.to_ascii_uppercase(),
+                    2,
+                    2 + font.dimensions.height,
+                    &yellow,
+                    0,
+                    false,
+                );
+                if let (Some(lap), Some(total_laps)) = (self.lap, self.total_laps) {
+                    let lap_text = format!("LAP {}/{}", lap, total_laps);
+                    canvas.draw_text(
+                        &font.led_font,
+                        &lap_text,
+                        width - font.get_text_dimensions(&lap_text).width - 2,
+                        2 + font.dimensions.height,
+                        &white,
+                        0,
+                        false,
+                    );
+                }
+                self.draw_running_order(canvas, font);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Sport;
+
+    #[test]
+    fn test_motorsport() {
+        let data = r#"{
+            "common": {
+                "sport_id": 9,
+                "home_team": {"id": "0", "display_name": "F1", "abbreviation": "F1", "primary_color": "e10600", "secondary_color": "ffffff"},
+                "away_team": {"id": "0", "display_name": "F1", "abbreviation": "F1", "primary_color": "e10600", "secondary_color": "ffffff"},
+                "home_score": 0,
+                "away_score": 0,
+                "status": "ACTIVE",
+                "ordinal": "",
+                "start_time": "2023-05-28T13:00:00Z",
+                "id": 1109
+            },
+            "name": "Monaco GP",
+            "session": "Race",
+            "lap": 44,
+            "total_laps": 78,
+            "flag": "SC",
+            "drivers": [
+                {"id": "1", "abbreviation": "VER", "position": 1, "gap": "", "team_color": "3671c6"},
+                {"id": "14", "abbreviation": "ALO", "position": 2, "gap": "+4.112", "team_color": "358c75"},
+                {"id": "31", "abbreviation": "OCO", "position": 3, "gap": "+9.530", "team_color": "2293d1"},
+                {"id": "44", "abbreviation": "HAM", "position": 4, "gap": "+11.204", "team_color": "6cd3bf"},
+                {"id": "63", "abbreviation": "RUS", "position": 5, "gap": "+14.870", "team_color": "6cd3bf"},
+                {"id": "16", "abbreviation": "LEC", "position": 6, "gap": "+1 LAP", "team_color": "f91536"},
+                {"id": "10", "abbreviation": "GAS", "position": 7, "gap": "+1 LAP", "team_color": "2293d1"}
+            ]
+        }"#;
+
+        let event: MotorsportEvent = serde_json::from_str(data).unwrap();
+        assert_eq!(event.flag, Some(FlagState::SafetyCar));
+        assert!(event.involves_team(44));
+        assert!(!event.involves_team(4));
+        assert_eq!(event.get_page_count(), 3);
+        let abbreviations: Vec<&str> = event
+            .get_page(1)
+            .iter()
+            .map(|driver| driver.abbreviation.as_str())
+            .collect();
+        assert_eq!(abbreviations, vec!["HAM", "RUS", "LEC"]);
+        assert_eq!(event.get_page(2).len(), 1);
+        assert!(event.get_page(3).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_flag() {
+        let flags: Vec<FlagState> = serde_json::from_str(r#"["RED", "VSC", "CHEQUERED"]"#).unwrap();
+        assert_eq!(
+            flags,
+            vec![FlagState::Red, FlagState::Unknown, FlagState::Unknown]
+        );
+        assert!(FlagState::Unknown.get_color().is_none());
+    }
+}
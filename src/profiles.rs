@@ -0,0 +1,196 @@
+use crate::common::{self, Profile, ProfileList, ProfileSchedule};
+use chrono::{Timelike, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// Named settings profiles, stored in profiles.json next to scoreboard_settings.json
+pub struct ProfileStore {
+    data: ProfileList,
+    schedule_generations: HashMap<String, u32>, // Bumped whenever a profile's schedule is replaced
+    pub file_path: PathBuf,
+    file_writes: common::FileWrites,
+}
+
+impl ProfileStore {
+    pub fn new(file_path: PathBuf, file_writes: common::FileWrites) -> ProfileStore {
+        let data = match fs::read_to_string(&file_path) {
+            Ok(profiles_string) => serde_json::from_str(&profiles_string).unwrap_or_else(|e| {
+                error!("Could not parse profiles at {:?}: {}", &file_path, e);
+                // Keep a copy, the next write replaces the file with an empty list
+                common::preserve_invalid_file::<ProfileList>(&file_path);
+                ProfileList::default()
+            }),
+            Err(_) => {
+                info!("No profiles found at {:?}", &file_path);
+                ProfileList::default()
+            }
+        };
+        ProfileStore {
+            data,
+            schedule_generations: HashMap::new(),
+            file_path,
+            file_writes,
+        }
+    }
+
+    fn write_profiles(&self) {
+        let result = serde_json::to_string_pretty(&self.data)
+            .map_err(|e| e.to_string())
+            .and_then(|profiles_string| {
+                self.file_writes
+                    .write(&self.file_path, &profiles_string)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            error!("Failed to write profiles to {:?}: {}", &self.file_path, e);
+        }
+    }
+
+    pub fn get_profiles(&self) -> ProfileList {
+        self.data.clone()
+    }
+
+    pub fn get_profile(&self, name: &str) -> Option<&Profile> {
+        self.data.profiles.iter().find(|profile| profile.name == name)
+    }
+
+    pub fn set_active(&mut self, name: Option<String>) {
+        self.data.active = name;
+        self.write_profiles();
+    }
+
+    // Insert a new profile, or replace the profile with the same name
+    pub fn save_profile(&mut self, profile: Profile) {
+        match self
+            .data
+            .profiles
+            .iter_mut()
+            .find(|existing| existing.name == profile.name)
+        {
+            Some(existing) => *existing = profile,
+            None => self.data.profiles.push(profile),
+        }
+        self.write_profiles();
+    }
+
+    pub fn delete_profile(&mut self, name: &str) -> bool {
+        let original_length = self.data.profiles.len();
+        self.data.profiles.retain(|profile| profile.name != name);
+        if self.data.active.as_deref() == Some(name) {
+            self.data.active = None;
+        }
+        // Bumped rather than removed, so a pending schedule can't match a recreated profile
+        self.next_schedule_generation(name);
+        self.write_profiles();
+        self.data.profiles.len() != original_length
+    }
+
+    // The profile the button should switch to: the one after the active profile, wrapping around
+    pub fn get_next_profile_name(&self) -> Option<String> {
+        let profiles = &self.data.profiles;
+        let next_index = match &self.data.active {
+            Some(active) => profiles
+                .iter()
+                .position(|profile| &profile.name == active)
+                .map(|index| (index + 1) % profiles.len())
+                .unwrap_or(0),
+            None => 0,
+        };
+        profiles.get(next_index).map(|profile| profile.name.clone())
+    }
+
+    pub fn next_schedule_generation(&mut self, name: &str) -> u32 {
+        let generation = self.schedule_generations.entry(name.to_owned()).or_insert(0);
+        *generation += 1;
+        *generation
+    }
+
+    pub fn is_current_schedule(&self, name: &str, generation: u32) -> bool {
+        self.schedule_generations.get(name) == Some(&generation)
+            && self
+                .get_profile(name)
+                .map(|profile| profile.schedule.is_some())
+                .unwrap_or(false)
+    }
+}
+
+// Time from now until the next occurrence of the schedule, in the scoreboard's timezone
+pub fn time_until_schedule(schedule: &ProfileSchedule, timezone: &Tz) -> Duration {
+    let now = Utc::now().with_timezone(timezone);
+    let seconds_now = now.num_seconds_from_midnight() as i64;
+    let seconds_target = schedule.hour.min(23) as i64 * 60 * 60 + schedule.minute.min(59) as i64 * 60;
+    let day = 24 * 60 * 60;
+    let delay = (seconds_target - seconds_now).rem_euclid(day);
+    Duration::from_secs(if delay == 0 { day } else { delay } as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::{ScoreboardSettingsData, ScreenId};
+
+    #[test]
+    fn test_partial_profile() {
+        let data = r#"{
+            "name": "Office",
+            "settings": {
+                "active_screen": 50,
+                "brightness": 20
+            },
+            "schedule": {
+                "hour": 8,
+                "minute": 30
+            }
+        }"#;
+        let profile: Profile = serde_json::from_str(data).unwrap();
+        assert_eq!(profile.settings.active_screen, Some(ScreenId::Clock));
+        assert_eq!(profile.settings.brightness, Some(20));
+        assert_eq!(profile.settings.timezone, None);
+        assert_eq!(profile.custom_message, None);
+
+        let settings: ScoreboardSettingsData =
+            serde_json::from_str(include_str!("../assets/demo/scoreboard_settings.json")).unwrap();
+        let applied = settings.apply_profile(&profile.settings);
+        assert_eq!(applied.active_screen, ScreenId::Clock);
+        assert_eq!(applied.brightness, 20);
+        assert_eq!(applied.timezone, settings.timezone);
+        assert_eq!(applied.screens, settings.screens);
+    }
+
+    #[test]
+    fn test_recreated_profile_schedule() {
+        let file_path = std::env::temp_dir().join("scoreboard_test_profiles.json");
+        let profile: Profile =
+            serde_json::from_str(r#"{"name": "Office", "schedule": {"hour": 8, "minute": 30}}"#)
+                .unwrap();
+        let mut store = ProfileStore::new(file_path.clone(), common::FileWrites::default());
+        store.save_profile(profile.clone());
+        let pending = store.next_schedule_generation("Office");
+        assert!(store.is_current_schedule("Office", pending));
+
+        assert!(store.delete_profile("Office"));
+        store.save_profile(profile);
+        let rescheduled = store.next_schedule_generation("Office");
+        assert!(!store.is_current_schedule("Office", pending));
+        assert!(store.is_current_schedule("Office", rescheduled));
+        let _ = fs::remove_file(file_path);
+    }
+
+    #[test]
+    fn test_invalid_profiles_preserved() {
+        let file_path = std::env::temp_dir().join("scoreboard_test_invalid_profiles.json");
+        let backup_path = file_path.with_extension("json.invalid");
+        fs::write(&file_path, r#"{"profiles": [{"name": "Off"#).unwrap();
+        let store = ProfileStore::new(file_path.clone(), common::FileWrites::default());
+        assert!(store.get_profiles().profiles.is_empty());
+        assert_eq!(
+            fs::read_to_string(&backup_path).unwrap(),
+            r#"{"profiles": [{"name": "Off"#
+        );
+        let _ = fs::remove_file(file_path);
+        let _ = fs::remove_file(backup_path);
+    }
+}
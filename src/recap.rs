@@ -0,0 +1,250 @@
+// Finished games are kept after they drop out of the payload so the board can recap the previous day
+use crate::common;
+use crate::game::{self, CommonGameData, GameStatus};
+use crate::matrix;
+
+use chrono::{Duration, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const RECAP_PATH: &str = "recap.json";
+const HOURS_KEPT: i64 = 48; // Enough to cover yesterday in any timezone
+const GAMES_PER_PAGE: usize = 2;
+const PAGE_TIME_SECONDS: i64 = 5;
+pub const RECAP_UNTIL_HOUR: u32 = 12; // The recap auto power mode only wakes the board in the morning
+
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct RecapStore {
+    games: Vec<serde_json::Value>, // The `common` object of each finished game
+}
+
+fn parse_common(value: &serde_json::Value) -> Option<CommonGameData> {
+    serde_json::from_value(value.clone()).ok()
+}
+
+impl RecapStore {
+    pub fn read(path: &Path) -> RecapStore {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|recap_string| serde_json::from_str(&recap_string).ok())
+            .unwrap_or_default()
+    }
+
+    // Adds the finished games in a payload and forgets games too old to recap, returns true if
+    // anything changed
+    pub fn record(&mut self, body: &str) -> bool {
+        let response: game::Response<serde_json::Value> = match serde_json::from_str(body) {
+            Ok(response) => response,
+            Err(_) => return false,
+        };
+        let previous_games = self.games.clone();
+        for mut game in response.data.games {
+            let value = game["common"].take();
+            let common = match parse_common(&value) {
+                Some(common) if common.status == GameStatus::End => common,
+                _ => continue,
+            };
+            let existing = self.games.iter().position(|existing| {
+                parse_common(existing).map_or(false, |existing| {
+                    (existing.sport_id, existing.id) == (common.sport_id, common.id)
+                })
+            });
+            match existing {
+                Some(index) => self.games[index] = value,
+                None => self.games.push(value),
+            }
+        }
+        let cutoff = Utc::now() - Duration::hours(HOURS_KEPT);
+        self.games
+            .retain(|value| parse_common(value).map_or(false, |common| common.start_time > cutoff));
+        self.games != previous_games
+    }
+
+    pub fn write(&self, path: &Path) {
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|recap_string| fs::write(path, recap_string).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Failed to write recap to {:?}: {}", path, e);
+        }
+    }
+
+    pub fn get_games(&self) -> Vec<CommonGameData> {
+        self.games.iter().filter_map(parse_common).collect()
+    }
+}
+
+fn has_favorite(common: &CommonGameData, settings: &common::ScoreboardSettingsData) -> bool {
+    settings
+        .get_focus_teams(common.sport_id)
+        .iter()
+        .any(|team_id| common.involves_team(*team_id))
+}
+
+// Games that started yesterday in the board's timezone, favorites first
+pub fn get_recap_games<'a>(
+    games: &'a [CommonGameData],
+    settings: &common::ScoreboardSettingsData,
+) -> Vec<&'a CommonGameData> {
+    let yesterday = Utc::now().with_timezone(&settings.timezone).date().pred();
+    let mut recap_games: Vec<&CommonGameData> = games
+        .iter()
+        .filter(|common| common.start_time.with_timezone(&settings.timezone).date() == yesterday)
+        .collect();
+    recap_games.sort_by_key(|common| (!has_favorite(common, settings), common.start_time));
+    recap_games
+}
+
+pub fn should_wake(games: &[&CommonGameData], timezone: &Tz) -> bool {
+    !games.is_empty() && Utc::now().with_timezone(timezone).hour() < RECAP_UNTIL_HOUR
+}
+
+// Favorites are green for a win and red for a loss, otherwise the loser is dimmed
+fn get_result_color(
+    team_id: u32,
+    score: u8,
+    other_score: u8,
+    favorites: &[u32],
+) -> rpi_led_matrix::LedColor {
+    match (favorites.contains(&team_id), score.cmp(&other_score)) {
+        (true, std::cmp::Ordering::Greater) => common::new_color(52, 162, 35),
+        (true, std::cmp::Ordering::Less) => common::new_color(200, 30, 30),
+        (false, std::cmp::Ordering::Less) => common::new_color(120, 120, 120),
+        _ => common::new_color(255, 255, 255),
+    }
+}
+
+// Pages through yesterday's results, returns false if there is nothing to show
+pub fn draw_recap(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    games: &[&CommonGameData],
+    settings: &common::ScoreboardSettingsData,
+) -> bool {
+    if games.is_empty() {
+        return false;
+    }
+    let (width, _height) = canvas.canvas_size();
+    let grey = common::new_color(120, 120, 120);
+    let green = common::new_color(52, 162, 35);
+    let pages = (games.len() + GAMES_PER_PAGE - 1) / GAMES_PER_PAGE;
+    let page = (Utc::now().timestamp() / PAGE_TIME_SECONDS) as usize % pages;
+
+    canvas.draw_text(
+        &font.led_font,
+        "YESTERDAY",
+        1,
+        font.dimensions.height,
+        &green,
+        0,
+        false,
+    );
+    if pages > 1 {
+        let page_text = format!("{}/{}", page + 1, pages);
+        canvas.draw_text(
+            &font.led_font,
+            &page_text,
+            width - font.get_text_dimensions(&page_text).width,
+            font.dimensions.height,
+            &grey,
+            0,
+            false,
+        );
+    }
+
+    let row_height = font.dimensions.height + 1;
+    for (i, common) in games
+        .iter()
+        .skip(page * GAMES_PER_PAGE)
+        .take(GAMES_PER_PAGE)
+        .enumerate()
+    {
+        let favorites = settings.get_focus_teams(common.sport_id);
+        let top = row_height + 1 + i as i32 * (2 * row_height + 1);
+        let rows = [
+            (&common.away_team, common.away_score, common.home_score),
+            (&common.home_team, common.home_score, common.away_score),
+        ];
+        for (row, (team, score, other_score)) in rows.iter().enumerate() {
+            let baseline = top + (row as i32 + 1) * row_height - 1;
+            let color = get_result_color(team.id, *score, *other_score, &favorites);
+            let score_text = score.to_string();
+            canvas.draw_text(
+                &font.led_font,
+                &team.abbreviation.to_ascii_uppercase(),
+                2,
+                baseline,
+                &color,
+                0,
+                false,
+            );
+            canvas.draw_text(
+                &font.led_font,
+                &score_text,
+                30 - font.get_text_dimensions(&score_text).width,
+                baseline,
+                &color,
+                0,
+                false,
+            );
+        }
+        // Like FINAL or F/OT
+        let ordinal = common.ordinal.to_ascii_uppercase();
+        canvas.draw_text(
+            &font.led_font,
+            &ordinal,
+            width - 1 - font.get_text_dimensions(&ordinal).width,
+            top + row_height - 1,
+            &grey,
+            0,
+            false,
+        );
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recap_store() {
+        let start_time = (Utc::now() - Duration::hours(3))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let finished = serde_json::json!({"common": {
+            "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+            "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+            "away_score": 2, "home_score": 3, "status": "END", "ordinal": "Final",
+            "start_time": start_time, "sport_id": 0, "id": 1
+        }});
+        let active = serde_json::json!({"common": {
+            "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+            "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+            "away_score": 2, "home_score": 3, "status": "ACTIVE", "ordinal": "3rd",
+            "start_time": start_time, "sport_id": 0, "id": 2
+        }});
+        let too_old = serde_json::json!({"common": {
+            "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+            "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+            "away_score": 2, "home_score": 3, "status": "END", "ordinal": "Final",
+            "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 3
+        }});
+
+        let mut store = RecapStore::default();
+        let body = serde_json::json!({"data": {"games": [finished, active, too_old]}});
+        assert!(store.record(&body.to_string()));
+        // Nothing new to write
+        let body = serde_json::json!({"data": {"games": [finished]}});
+        assert!(!store.record(&body.to_string()));
+        let games = store.get_games();
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].id, 1);
+
+        let favorites = vec![19];
+        assert_eq!(get_result_color(19, 3, 2, &favorites).green, 162);
+        assert_eq!(get_result_color(25, 2, 3, &favorites).red, 120);
+    }
+}
@@ -0,0 +1,195 @@
+// Compares successive refreshes game by game so the board knows what just happened
+use crate::common;
+use crate::game::{self, GameStatus};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreEventKind {
+    ScoreChanged { team_id: u32, points: u8 },
+    GameStarted,
+    PeriodChanged { ordinal: String },
+    GameFinal,
+    PowerPlayStarted { team_id: u32 },
+    RunnerInScoringPosition,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreEvent {
+    pub sport_id: common::ScreenId,
+    pub game_id: usize,
+    pub kind: ScoreEventKind,
+}
+
+pub fn diff_game(old: &dyn game::Sport, new: &dyn game::Sport) -> Vec<ScoreEvent> {
+    let (old_common, new_common) = (old.get_common(), new.get_common());
+    let mut kinds = Vec::new();
+
+    if old_common.status == GameStatus::Pregame && new_common.is_active_game() {
+        kinds.push(ScoreEventKind::GameStarted);
+    }
+    // Scores that go down are corrections, not something worth reacting to
+    for (team, old_score, new_score) in [
+        (
+            &new_common.away_team,
+            old_common.away_score,
+            new_common.away_score,
+        ),
+        (
+            &new_common.home_team,
+            old_common.home_score,
+            new_common.home_score,
+        ),
+    ]
+    .iter()
+    {
+        if new_score > old_score {
+            kinds.push(ScoreEventKind::ScoreChanged {
+                team_id: team.id,
+                points: new_score - old_score,
+            });
+        }
+    }
+    if old_common.is_active_game()
+        && new_common.is_active_game()
+        && old_common.ordinal != new_common.ordinal
+    {
+        kinds.push(ScoreEventKind::PeriodChanged {
+            ordinal: new_common.ordinal.clone(),
+        });
+    }
+    if old_common.status != GameStatus::End && new_common.status == GameStatus::End {
+        kinds.push(ScoreEventKind::GameFinal);
+    }
+
+    let (old_situation, new_situation) = (old.get_situation(), new.get_situation());
+    if let Some(team_id) = new_situation.powerplay_team {
+        if old_situation.powerplay_team != Some(team_id) {
+            kinds.push(ScoreEventKind::PowerPlayStarted { team_id });
+        }
+    }
+    if new_situation.runner_in_scoring_position && !old_situation.runner_in_scoring_position {
+        kinds.push(ScoreEventKind::RunnerInScoringPosition);
+    }
+
+    kinds
+        .into_iter()
+        .map(|kind| ScoreEvent {
+            sport_id: new_common.sport_id,
+            game_id: new_common.id,
+            kind,
+        })
+        .collect()
+}
+
+// Games are matched by sport and id, games that only appear on one side have nothing to compare
+pub fn diff_games<'a>(
+    old_games: impl Iterator<Item = &'a dyn game::Sport>,
+    new_games: impl Iterator<Item = &'a dyn game::Sport>,
+) -> Vec<ScoreEvent> {
+    let old_games: HashMap<(u16, usize), &dyn game::Sport> =
+        old_games.map(|game| (game.get_game_key(), game)).collect();
+    new_games
+        .filter_map(|new| {
+            old_games
+                .get(&new.get_game_key())
+                .map(|old| diff_game(*old, new))
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hockey::HockeyGame;
+
+    #[test]
+    fn test_diff_games() {
+        let pregame: HockeyGame = serde_json::from_str(
+            r#"{
+            "common": {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 1, "status": "PREGAME", "ordinal": "",
+                "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 1
+            },
+            "away_powerplay": false,
+            "home_powerplay": false,
+            "away_players": 5,
+            "home_players": 5
+        }"#,
+        )
+        .unwrap();
+        let first: HockeyGame = serde_json::from_str(
+            r#"{
+            "common": {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 1, "status": "ACTIVE", "ordinal": "1st",
+                "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 1
+            },
+            "away_powerplay": false,
+            "home_powerplay": false,
+            "away_players": 5,
+            "home_players": 5
+        }"#,
+        )
+        .unwrap();
+        let second: HockeyGame = serde_json::from_str(
+            r#"{
+            "common": {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 2, "home_score": 1, "status": "ACTIVE", "ordinal": "2nd",
+                "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 1
+            },
+            "away_powerplay": false,
+            "home_powerplay": true,
+            "away_players": 4,
+            "home_players": 5
+        }"#,
+        )
+        .unwrap();
+        let last: HockeyGame = serde_json::from_str(
+            r#"{
+            "common": {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 2, "home_score": 1, "status": "END", "ordinal": "Final",
+                "start_time": "2020-01-01T01:00:00Z", "sport_id": 0, "id": 1
+            },
+            "away_powerplay": false,
+            "home_powerplay": false,
+            "away_players": 5,
+            "home_players": 5
+        }"#,
+        )
+        .unwrap();
+
+        let kinds = |old: &HockeyGame, new: &HockeyGame| -> Vec<ScoreEventKind> {
+            diff_games(
+                vec![old as &dyn game::Sport].into_iter(),
+                vec![new as &dyn game::Sport].into_iter(),
+            )
+            .into_iter()
+            .map(|event| event.kind)
+            .collect()
+        };
+        assert_eq!(kinds(&pregame, &first), vec![ScoreEventKind::GameStarted]);
+        assert_eq!(
+            kinds(&first, &second),
+            vec![
+                ScoreEventKind::ScoreChanged {
+                    team_id: 25,
+                    points: 2
+                },
+                ScoreEventKind::PeriodChanged {
+                    ordinal: "2nd".to_string()
+                },
+                ScoreEventKind::PowerPlayStarted { team_id: 19 },
+            ]
+        );
+        assert_eq!(kinds(&second, &last), vec![ScoreEventKind::GameFinal]);
+        assert_eq!(kinds(&second, &second), vec![]);
+    }
+}
@@ -0,0 +1,476 @@
+// Where the smart screen gets its scores from. Every source hands back a raw `all_v4` style
+// JSON payload, `{"data": {"games": [...]}}`, which the refresh thread parses the same way.
+use crate::config;
+use crate::game;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    Dns,
+    Timeout,
+    Connection,
+    ClientError(u16),
+    ServerError(u16),
+    InvalidApiKey,
+    Parse,
+    Unavailable, // A local source couldn't be read
+}
+
+impl FetchError {
+    // Short enough to fit on one line of the matrix
+    pub fn describe(&self) -> String {
+        match self {
+            FetchError::Dns => "DNS lookup failed".to_string(),
+            FetchError::Timeout => "Timed out".to_string(),
+            FetchError::Connection => "No connection".to_string(),
+            FetchError::ClientError(status) => format!("Request error {}", status),
+            FetchError::ServerError(status) => format!("Server error {}", status),
+            FetchError::InvalidApiKey => "Invalid API key".to_string(),
+            FetchError::Parse => "Invalid data".to_string(),
+            FetchError::Unavailable => "Source unavailable".to_string(),
+        }
+    }
+
+    // Errors that retrying won't fix on their own
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, FetchError::InvalidApiKey)
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.describe())
+    }
+}
+
+// Response headers that let the next request ask for the payload only if it changed
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone, Default)]
+pub struct CacheValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub enum Fetched {
+    Updated { body: String, bytes: usize }, // bytes is what came over the network, before decompression
+    NotModified,
+}
+
+pub trait ScoreSource: Send {
+    // Sources that support conditional requests read and update `validators`
+    fn fetch(&mut self, validators: &mut CacheValidators) -> Result<Fetched, FetchError>;
+    fn describe(&self) -> String;
+}
+
+// The Scoreboard API, authenticated with the key from secrets.txt
+pub struct ApiSource {
+    base_url: String,
+    api_key: String,
+}
+
+impl ApiSource {
+    pub fn new(base_url: String, api_key: String) -> ApiSource {
+        ApiSource { base_url, api_key }
+    }
+}
+
+impl ScoreSource for ApiSource {
+    fn fetch(&mut self, validators: &mut CacheValidators) -> Result<Fetched, FetchError> {
+        read_response(
+            game::fetch_games(&self.base_url, "all_v4", &self.api_key, validators),
+            validators,
+            true,
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!("API at {}", self.base_url)
+    }
+}
+
+// Any server that serves an all_v4 payload at a fixed URL without an API key, like a local aggregator
+pub struct HttpSource {
+    url: String,
+}
+
+impl HttpSource {
+    pub fn new(url: String) -> HttpSource {
+        HttpSource { url }
+    }
+}
+
+impl ScoreSource for HttpSource {
+    fn fetch(&mut self, validators: &mut CacheValidators) -> Result<Fetched, FetchError> {
+        read_response(
+            game::conditional_get(&self.url, validators).call(),
+            validators,
+            false,
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!("HTTP server at {}", self.url)
+    }
+}
+
+// A directory of payload files, the games and standings of which are merged together
+pub struct DirectorySource {
+    path: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(path: PathBuf) -> DirectorySource {
+        DirectorySource { path }
+    }
+}
+
+impl ScoreSource for DirectorySource {
+    fn fetch(&mut self, _validators: &mut CacheValidators) -> Result<Fetched, FetchError> {
+        let mut file_paths: Vec<PathBuf> = fs::read_dir(&self.path)
+            .map_err(|e| {
+                error!("Could not read {:?}: {}", self.path, e);
+                FetchError::Unavailable
+            })?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .map_or(false, |extension| extension == "json")
+            })
+            .collect();
+        file_paths.sort();
+
+        let mut games: Vec<serde_json::Value> = Vec::new();
+        let mut standings: Vec<serde_json::Value> = Vec::new();
+        let mut upcoming: Vec<serde_json::Value> = Vec::new();
+        for file_path in file_paths {
+            let file_string = fs::read_to_string(&file_path).map_err(|e| {
+                error!("Could not read {:?}: {}", file_path, e);
+                FetchError::Unavailable
+            })?;
+            let mut response: serde_json::Value =
+                serde_json::from_str(&file_string).map_err(|e| {
+                    error!("Could not parse {:?}: {}", file_path, e);
+                    FetchError::Parse
+                })?;
+            match response["data"]["games"].take() {
+                serde_json::Value::Array(file_games) => games.extend(file_games),
+                _ => warn!("No games found in {:?}", file_path),
+            }
+            if let serde_json::Value::Array(file_standings) = response["data"]["standings"].take() {
+                standings.extend(file_standings);
+            }
+            if let serde_json::Value::Array(file_upcoming) = response["data"]["upcoming"].take() {
+                upcoming.extend(file_upcoming);
+            }
+        }
+        Ok(Fetched::Updated {
+            body: serde_json::json!({
+                "data": { "games": games, "standings": standings, "upcoming": upcoming }
+            })
+            .to_string(),
+            bytes: 0,
+        })
+    }
+
+    fn describe(&self) -> String {
+        format!("directory {:?}", self.path)
+    }
+}
+
+// The payload bundled into the demo binary
+pub struct DemoSource;
+
+impl ScoreSource for DemoSource {
+    fn fetch(&mut self, _validators: &mut CacheValidators) -> Result<Fetched, FetchError> {
+        Ok(Fetched::Updated {
+            body: include_str!("../assets/demo/demo_scores.json").to_string(),
+            bytes: 0,
+        })
+    }
+
+    fn describe(&self) -> String {
+        "demo games".to_string()
+    }
+}
+
+pub fn classify_io_error(e: &io::Error) -> FetchError {
+    match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => FetchError::Timeout,
+        _ => FetchError::Connection,
+    }
+}
+
+// Only a source that sends the API key can blame it for a 401 or 403
+pub fn classify_response(resp: &ureq::Response, uses_api_key: bool) -> FetchError {
+    if let Some(synthetic_error) = resp.synthetic_error() {
+        return match synthetic_error {
+            ureq::Error::DnsFailed(_) => FetchError::Dns,
+            ureq::Error::Io(e) => classify_io_error(e),
+            _ => FetchError::Connection,
+        };
+    }
+    match resp.status() {
+        401 | 403 if uses_api_key => FetchError::InvalidApiKey,
+        status if resp.client_error() => FetchError::ClientError(status),
+        status => FetchError::ServerError(status),
+    }
+}
+
+// Gzip is a raw deflate stream behind a header that may carry optional fields
+fn decode_gzip(data: &[u8]) -> Option<Vec<u8>> {
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+    const FHCRC: u8 = 0x02;
+    if data.len() < 18 || data[0] != 0x1f || data[1] != 0x8b || data[2] != 8 {
+        return None;
+    }
+    let flags = data[3];
+    let mut index = 10;
+    if flags & FEXTRA != 0 {
+        let extra_length = *data.get(index)? as usize | (*data.get(index + 1)? as usize) << 8;
+        index += 2 + extra_length;
+    }
+    for flag in [FNAME, FCOMMENT].iter() {
+        if flags & flag != 0 {
+            index += data.get(index..)?.iter().position(|byte| *byte == 0)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        index += 2;
+    }
+    miniz_oxide::inflate::decompress_to_vec(data.get(index..data.len() - 8)?).ok()
+}
+
+fn decode_body(content_encoding: Option<&str>, data: Vec<u8>) -> Option<Vec<u8>> {
+    match content_encoding.map(|encoding| encoding.trim().to_ascii_lowercase()) {
+        Some(encoding) if encoding == "gzip" => decode_gzip(&data),
+        // Servers disagree on whether deflate means zlib wrapped or raw
+        Some(encoding) if encoding == "deflate" => {
+            miniz_oxide::inflate::decompress_to_vec_zlib(&data)
+                .or_else(|_| miniz_oxide::inflate::decompress_to_vec(&data))
+                .ok()
+        }
+        _ => Some(data),
+    }
+}
+
+fn read_response(
+    resp: ureq::Response,
+    validators: &mut CacheValidators,
+    uses_api_key: bool,
+) -> Result<Fetched, FetchError> {
+    if resp.status() == 304 {
+        info!("Games have not changed since the last fetch");
+        return Ok(Fetched::NotModified);
+    }
+    if resp.error() {
+        let fetch_error = classify_response(&resp, uses_api_key);
+        error!(
+            "There was an error fetching games: {} ({} {})",
+            fetch_error,
+            resp.status(),
+            resp.status_text()
+        );
+        return Err(fetch_error);
+    }
+    debug!(
+        "Fetched games: {} {:?}",
+        resp.status(),
+        resp.headers_names()
+            .iter()
+            .map(|name| (name.as_str(), resp.header(name).unwrap_or_default()))
+            .collect::<Vec<_>>()
+    );
+    *validators = CacheValidators {
+        etag: resp.header("ETag").map(str::to_string),
+        last_modified: resp.header("Last-Modified").map(str::to_string),
+    };
+    let content_encoding = resp.header("Content-Encoding").map(str::to_string);
+    let mut data = Vec::new();
+    resp.into_reader().read_to_end(&mut data).map_err(|e| {
+        error!("Failed to read response body {:?}", e);
+        classify_io_error(&e)
+    })?;
+    let bytes = data.len();
+    let body = decode_body(content_encoding.as_deref(), data)
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .ok_or_else(|| {
+            error!(
+                "Failed to decode response body with encoding {:?}",
+                content_encoding
+            );
+            FetchError::Parse
+        })?;
+    Ok(Fetched::Updated { body, bytes })
+}
+
+pub fn from_config(config: &config::ScoreboardConfig, root_path: &Path) -> Box<dyn ScoreSource> {
+    match &config.source {
+        config::SourceConfig::Api => Box::new(ApiSource::new(
+            config.api.url.clone(),
+            config.get_api_key(root_path),
+        )),
+        config::SourceConfig::Http { url } => Box::new(HttpSource::new(url.clone())),
+        config::SourceConfig::Directory { path } => Box::new(DirectorySource::new(path.clone())),
+        config::SourceConfig::Demo => Box::new(DemoSource),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    // Answers a single request with `response` and hands back the request it received
+    fn serve_once(response: &'static [u8]) -> (String, std::thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/all_v4", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let length = stream.read(&mut request).unwrap();
+            stream.write_all(response).unwrap();
+            String::from_utf8_lossy(&request[..length]).into_owned()
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn test_http_source() {
+        let (url, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\n\
+            ETag: \"v1\"\r\n\
+            Content-Length: 23\r\n\
+            Connection: close\r\n\r\n\
+            {\"data\": {\"games\": []}}",
+        );
+        let mut source = HttpSource::new(url);
+        let mut validators = CacheValidators::default();
+        match source.fetch(&mut validators) {
+            Ok(Fetched::Updated { body, bytes }) => {
+                assert_eq!(body, r#"{"data": {"games": []}}"#);
+                assert_eq!(bytes, 23);
+            }
+            _ => panic!("Expected a payload"),
+        }
+        server.join().unwrap();
+        assert_eq!(validators.etag, Some("\"v1\"".to_string()));
+
+        // The next request carries the ETag and the server says nothing changed
+        let (url, server) = serve_once(b"HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n");
+        source.url = url;
+        assert!(matches!(
+            source.fetch(&mut validators),
+            Ok(Fetched::NotModified)
+        ));
+        assert!(server.join().unwrap().contains("If-None-Match: \"v1\""));
+
+        let (url, server) =
+            serve_once(b"HTTP/1.1 503 Service Unavailable\r\nConnection: close\r\n\r\n");
+        source.url = url;
+        assert_eq!(
+            source.fetch(&mut validators).err(),
+            Some(FetchError::ServerError(503))
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_classify_response() {
+        let unauthorized = ureq::Response::new(401, "Unauthorized", "");
+        assert_eq!(
+            classify_response(&unauthorized, true),
+            FetchError::InvalidApiKey
+        );
+        assert_eq!(
+            classify_response(&unauthorized, false),
+            FetchError::ClientError(401)
+        );
+        assert_eq!(
+            classify_response(&ureq::Response::new(404, "Not Found", ""), true),
+            FetchError::ClientError(404)
+        );
+        assert_eq!(
+            classify_response(&ureq::Response::new(502, "Bad Gateway", ""), true),
+            FetchError::ServerError(502)
+        );
+
+        // Nothing is listening on the port once the listener is dropped
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let refused = ureq::get(&format!("http://127.0.0.1:{}/all_v4", port)).call();
+        assert_eq!(classify_response(&refused, true), FetchError::Connection);
+    }
+
+    #[test]
+    fn test_directory_source() {
+        let path = std::env::temp_dir().join("scoreboard_test_directory_source");
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("a.json"),
+            r#"{"data": {"games": [{"id": 1}], "standings": [{"id": 7}]}}"#,
+        )
+        .unwrap();
+        fs::write(
+            path.join("b.json"),
+            r#"{"data": {"games": [{"id": 2}], "upcoming": [{"id": 8}]}}"#,
+        )
+        .unwrap();
+        fs::write(path.join("notes.txt"), "not a payload").unwrap();
+
+        let mut source = DirectorySource::new(path.clone());
+        let body = match source.fetch(&mut CacheValidators::default()) {
+            Ok(Fetched::Updated { body, .. }) => body,
+            _ => panic!("Expected a payload"),
+        };
+        let response: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            response["data"]["games"],
+            serde_json::json!([{"id": 1}, {"id": 2}])
+        );
+        assert_eq!(
+            response["data"]["standings"],
+            serde_json::json!([{"id": 7}])
+        );
+        assert_eq!(response["data"]["upcoming"], serde_json::json!([{"id": 8}]));
+
+        fs::write(path.join("c.json"), "{").unwrap();
+        assert_eq!(
+            source.fetch(&mut CacheValidators::default()).err(),
+            Some(FetchError::Parse)
+        );
+        fs::remove_dir_all(&path).unwrap();
+        assert_eq!(
+            source.fetch(&mut CacheValidators::default()).err(),
+            Some(FetchError::Unavailable)
+        );
+    }
+
+    #[test]
+    fn test_decode_body() {
+        let body = br#"{"data": {"games": []}}"#;
+        let deflated = miniz_oxide::deflate::compress_to_vec(body, 6);
+        let mut gzipped = vec![0x1f, 0x8b, 8, 0x08, 0, 0, 0, 0, 0, 0xff]; // FNAME set
+        gzipped.extend_from_slice(b"all_v4.json\0");
+        gzipped.extend_from_slice(&deflated);
+        gzipped.extend_from_slice(&[0; 8]); // CRC and length aren't checked
+        assert_eq!(decode_body(Some("gzip"), gzipped).unwrap(), body.to_vec());
+
+        let zlib = miniz_oxide::deflate::compress_to_vec_zlib(body, 6);
+        assert_eq!(decode_body(Some("deflate"), zlib).unwrap(), body.to_vec());
+        assert_eq!(
+            decode_body(Some("deflate"), deflated).unwrap(),
+            body.to_vec()
+        );
+        assert_eq!(decode_body(None, body.to_vec()).unwrap(), body.to_vec());
+    }
+}
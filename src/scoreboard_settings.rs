@@ -1,19 +1,27 @@
-use crate::common::{self, ScoreboardSettingsData, ScreenId, SetupState};
+use crate::common::{self, ScoreboardSettingsData, ScoreboardSettingsOverlay, ScreenId, SetupState};
 use chrono_tz::Tz;
-use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-#[derive(PartialEq, Debug)]
+#[derive(Debug)]
 pub struct ScoreboardSettings {
     data: Arc<ScoreboardSettingsData>,
     pub file_path: PathBuf,
+    pub file_writes: common::FileWrites,
 }
 
 impl ScoreboardSettings {
-    pub fn new(data: Arc<ScoreboardSettingsData>, file_path: PathBuf) -> ScoreboardSettings {
-        ScoreboardSettings { data, file_path }
+    pub fn new(
+        data: Arc<ScoreboardSettingsData>,
+        file_path: PathBuf,
+        file_writes: common::FileWrites,
+    ) -> ScoreboardSettings {
+        ScoreboardSettings {
+            data,
+            file_path,
+            file_writes,
+        }
     }
 
     pub fn get_settings(&self) -> Arc<ScoreboardSettingsData> {
@@ -21,11 +29,13 @@ impl ScoreboardSettings {
     }
 
     fn write_settings(&self) {
-        fs::write(
-            &self.file_path,
-            serde_json::to_string_pretty(&self.data).unwrap(),
-        )
-        .unwrap();
+        common::preserve_invalid_file::<ScoreboardSettingsData>(&self.file_path);
+        self.file_writes
+            .write(
+                &self.file_path,
+                &serde_json::to_string_pretty(&self.data).unwrap(),
+            )
+            .unwrap();
     }
 
     pub fn update_settings(&mut self, new_settings: ScoreboardSettingsData) {
@@ -33,6 +43,16 @@ impl ScoreboardSettings {
         self.write_settings();
     }
 
+    pub fn set_settings(&mut self, new_settings: ScoreboardSettingsData) {
+        self.data = Arc::from(new_settings);
+        self.write_settings();
+    }
+
+    pub fn apply_profile(&mut self, overlay: &ScoreboardSettingsOverlay) {
+        self.data = Arc::from(self.data.apply_profile(overlay));
+        self.write_settings();
+    }
+
     pub fn get_active_screen(&self) -> &ScreenId {
         &self.data.active_screen
     }
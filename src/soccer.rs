@@ -0,0 +1,209 @@
+use crate::aws_screen;
+use crate::common;
+use crate::game;
+use crate::matrix;
+
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SoccerData {
+    pub minute: Option<u8>, // Stops at 45, 90, 105 or 120 once stoppage time starts
+    pub stoppage_minute: Option<u8>, // Minutes played into stoppage time
+    #[serde(default)]
+    pub away_red_cards: u8,
+    #[serde(default)]
+    pub home_red_cards: u8,
+    pub away_aggregate: Option<u8>, // Two-leg ties only
+    pub home_aggregate: Option<u8>,
+    pub away_shootout: Option<u8>, // Penalty shootout goals
+    pub home_shootout: Option<u8>,
+}
+
+impl SoccerData {
+    pub fn get_clock_text(&self) -> Option<String> {
+        match (self.minute, self.stoppage_minute) {
+            (Some(minute), Some(stoppage_minute)) if stoppage_minute > 0 => {
+                Some(format!("{}+{}'", minute, stoppage_minute))
+            }
+            (Some(minute), _) => Some(format!("{}'", minute)),
+            _ => None,
+        }
+    }
+
+    pub fn get_aggregate_text(&self) -> Option<String> {
+        match (self.away_aggregate, self.home_aggregate) {
+            (Some(away), Some(home)) => Some(format!("AGG {}-{}", away, home)),
+            _ => None,
+        }
+    }
+
+    pub fn get_shootout_text(&self) -> Option<String> {
+        match (self.away_shootout, self.home_shootout) {
+            (Some(away), Some(home)) => Some(format!("PENS {}-{}", away, home)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SoccerGame {
+    pub common: game::CommonGameData,
+    pub extra_data: Option<SoccerData>,
+}
+
+impl Ord for SoccerGame {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.common.cmp(&other.common)
+    }
+}
+
+impl PartialOrd for SoccerGame {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SoccerGame {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.common,) == (&other.common,)
+    }
+}
+
+impl Eq for SoccerGame {}
+
+impl game::Sport for SoccerGame {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+}
+
+// Red cards are drawn as small bars to the left of the team's score
+fn draw_red_cards(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    score: u8,
+    red_cards: u8,
+    y_offset: i32,
+) {
+    let (width, _height) = canvas.canvas_size();
+    let red = common::new_color(255, 0, 0);
+    let score_width = font.get_text_dimensions(&score.to_string()).width;
+    for i in 0..std::cmp::min(red_cards, 3) as i32 {
+        let x = width - 6 - score_width - 3 * i;
+        matrix::draw_rectangle(canvas, (x, y_offset + 2), (x + 1, y_offset + 7), &red);
+    }
+}
+
+impl aws_screen::AWSScreenType for SoccerGame {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        let font = &font_book.font4x6;
+        let (width, _height) = canvas.canvas_size();
+        let spacing = 1;
+        game::draw_scoreboard(canvas, font, &self.common, spacing, (2, 2));
+
+        let white = common::new_color(255, 255, 255);
+        let yellow = common::new_color(255, 255, 0);
+        let top_row_height = 17;
+        let bottom_row_height = 25;
+        let left_indent = 2;
+        let right_indent = width - 1;
+        let mut draw_info = |text: &str, position: (i32, i32), color: &rpi_led_matrix::LedColor| {
+            canvas.draw_text(
+                &font.led_font,
+                text,
+                position.0,
+                position.1 + font.dimensions.height,
+                color,
+                0,
+                false,
+            );
+        };
+        let right_aligned = |text: &str| right_indent - font.get_text_dimensions(text).width;
+
+        draw_info(
+            &self.common.get_ordinal_text(timezone),
+            (left_indent, bottom_row_height),
+            &white,
+        );
+        if self.common.status == game::GameStatus::End {
+            draw_info(
+                "FINAL",
+                (right_aligned("FINAL"), bottom_row_height),
+                &yellow,
+            );
+        }
+
+        if let Some(soccer_data) = &self.extra_data {
+            // A shootout replaces the clock, which has stopped by then
+            let top_left = match soccer_data.get_shootout_text() {
+                Some(shootout_text) => Some(shootout_text),
+                None if self.common.status == game::GameStatus::Active => {
+                    soccer_data.get_clock_text()
+                }
+                None => None,
+            };
+            if let Some(text) = top_left {
+                draw_info(&text, (left_indent, top_row_height), &white);
+            }
+            if let Some(aggregate_text) = soccer_data.get_aggregate_text() {
+                draw_info(
+                    &aggregate_text,
+                    (right_aligned(&aggregate_text), top_row_height),
+                    &white,
+                );
+            }
+
+            let box_height = font.dimensions.height + 2 * spacing;
+            draw_red_cards(
+                canvas,
+                font,
+                self.common.away_score,
+                soccer_data.away_red_cards,
+                0,
+            );
+            draw_red_cards(
+                canvas,
+                font,
+                self.common.home_score,
+                soccer_data.home_red_cards,
+                box_height,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soccer_texts() {
+        let data = r#"{
+            "minute": 90,
+            "stoppage_minute": 4,
+            "home_red_cards": 1,
+            "away_aggregate": 2,
+            "home_aggregate": 3,
+            "away_shootout": null,
+            "home_shootout": null
+        }"#;
+
+        let soccer_data: SoccerData = serde_json::from_str(data).unwrap();
+        assert_eq!(soccer_data.away_red_cards, 0);
+        assert_eq!(soccer_data.home_red_cards, 1);
+        assert_eq!(soccer_data.get_clock_text(), Some("90+4'".to_string()));
+        assert_eq!(
+            soccer_data.get_aggregate_text(),
+            Some("AGG 2-3".to_string())
+        );
+        assert_eq!(soccer_data.get_shootout_text(), None);
+    }
+}
@@ -0,0 +1,199 @@
+// Tiles several games on the panel at once with a compact mini scoreboard for each
+use crate::common;
+use crate::game::{CommonGameData, GameStatus, Team};
+use crate::matrix;
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+// Top left corner and size of one game's tile
+pub type Tile = ((i32, i32), (i32, i32));
+
+// 2 stacked games on a 64x32 panel, a 2x2 grid on anything bigger
+pub fn get_tiles(canvas_size: (i32, i32)) -> Vec<Tile> {
+    let (width, height) = canvas_size;
+    if width >= 128 || height >= 64 {
+        let size = (width / 2, height / 2);
+        vec![
+            ((0, 0), size),
+            ((width / 2, 0), size),
+            ((0, height / 2), size),
+            ((width / 2, height / 2), size),
+        ]
+    } else {
+        let size = (width, height / 2);
+        vec![((0, 0), size), ((0, height / 2), size)]
+    }
+}
+
+// Active games first with the closest ones ahead, then by start time. The sort is stable, so
+// tiles only move between refreshes when a margin changes
+pub fn get_preference(common: &CommonGameData) -> (bool, Option<i16>, DateTime<Utc>) {
+    let active = common.is_active_game();
+    let margin = (common.away_score as i16 - common.home_score as i16).abs();
+    (!active, active.then(|| margin), common.start_time)
+}
+
+fn draw_team_row(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    team: &Team,
+    score: u8,
+    (x, top): (i32, i32),
+    row_height: i32,
+    color: &rpi_led_matrix::LedColor,
+) {
+    let baseline = top + (row_height + font.dimensions.height) / 2;
+    let score_text = score.to_string();
+    matrix::draw_rectangle(
+        canvas,
+        (x, top),
+        (x + 1, top + row_height - 1),
+        &team.primary_color,
+    );
+    canvas.draw_text(
+        &font.led_font,
+        &team.abbreviation.to_ascii_uppercase(),
+        x + 3,
+        baseline,
+        color,
+        0,
+        false,
+    );
+    canvas.draw_text(
+        &font.led_font,
+        &score_text,
+        x + 29 - font.get_text_dimensions(&score_text).width,
+        baseline,
+        color,
+        0,
+        false,
+    );
+}
+
+// Team color strips, abbreviations and scores, with the ordinal to the right when the tile is
+// wide and underneath when it is tall
+pub fn draw_mini_scoreboard(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    common: &CommonGameData,
+    timezone: &Tz,
+    tile: Tile,
+) {
+    let ((x, y), (width, height)) = tile;
+    let white = common::new_color(255, 255, 255);
+    let grey = common::new_color(120, 120, 120);
+    let yellow = common::new_color(255, 255, 0);
+    let ordinal_below = height >= 3 * (font.dimensions.height + 2);
+    let row_height = match ordinal_below {
+        true => height / 3,
+        false => height / 2,
+    };
+
+    // The loser of a finished game is dimmed
+    let rows = [
+        (&common.away_team, common.away_score, common.home_score),
+        (&common.home_team, common.home_score, common.away_score),
+    ];
+    for (i, (team, score, other_score)) in rows.iter().enumerate() {
+        let color = match common.status == GameStatus::End && score < other_score {
+            true => &grey,
+            false => &white,
+        };
+        draw_team_row(
+            canvas,
+            font,
+            team,
+            *score,
+            (x, y + i as i32 * row_height),
+            row_height,
+            color,
+        );
+    }
+
+    let ordinal_color = match common.is_active_game() {
+        true => &yellow,
+        false => &grey,
+    };
+    let (ordinal_x, ordinal_baseline) = match ordinal_below {
+        true => (
+            x + 3,
+            y + 2 * row_height + (row_height + font.dimensions.height) / 2,
+        ),
+        false => (x + 32, y + (height + font.dimensions.height) / 2),
+    };
+    // Cut long ordinals short so they don't run into the next tile
+    let max_chars = std::cmp::max((x + width - ordinal_x) / font.dimensions.width, 0) as usize;
+    let ordinal: String = common
+        .get_ordinal_text(timezone)
+        .to_ascii_uppercase()
+        .chars()
+        .take(max_chars)
+        .collect();
+    canvas.draw_text(
+        &font.led_font,
+        &ordinal,
+        ordinal_x,
+        ordinal_baseline,
+        ordinal_color,
+        0,
+        false,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_view() {
+        assert_eq!(
+            get_tiles((64, 32)),
+            vec![((0, 0), (64, 16)), ((0, 16), (64, 16))]
+        );
+        assert_eq!(get_tiles((128, 32)).len(), 4);
+        assert_eq!(get_tiles((64, 64))[3], ((32, 32), (32, 32)));
+
+        let mut games: Vec<CommonGameData> = serde_json::from_str(
+            r#"[
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 0, "status": "END", "ordinal": "",
+                "start_time": "2020-01-01T18:00:00Z", "sport_id": 0, "id": 1
+            },
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 1, "home_score": 2, "status": "ACTIVE", "ordinal": "",
+                "start_time": "2020-01-01T20:00:00Z", "sport_id": 0, "id": 2
+            },
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 0, "home_score": 0, "status": "PREGAME", "ordinal": "",
+                "start_time": "2020-01-01T23:00:00Z", "sport_id": 0, "id": 3
+            },
+            {
+                "home_team": {"id": "19", "display_name": "Blues", "abbreviation": "STL", "primary_color": "002f87", "secondary_color": "ffb81c"},
+                "away_team": {"id": "25", "display_name": "Stars", "abbreviation": "DAL", "primary_color": "006341", "secondary_color": "a2aaad"},
+                "away_score": 7, "home_score": 1, "status": "INTERMISSION", "ordinal": "",
+                "start_time": "2020-01-01T19:00:00Z", "sport_id": 0, "id": 4
+            }
+        ]"#,
+        )
+        .unwrap();
+        // The one goal game goes ahead of the blowout that started earlier
+        games.sort_by_key(get_preference);
+        let statuses: Vec<_> = games.iter().map(|game| game.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                GameStatus::Active,
+                GameStatus::Intermission,
+                GameStatus::End,
+                GameStatus::Pregame,
+            ]
+        );
+    }
+}
@@ -1,25 +1,46 @@
 // Draw a sport
 use crate::baseball::BaseballGame;
 use crate::basketball::{BasketballGame, CollegeBasketballGame};
+use crate::celebration::{Celebration, CELEBRATION_COOLDOWN};
 use crate::common;
+use crate::countdown::{self, NextGame};
+use crate::cricket::CricketGame;
 use crate::football::{CollegeFootballGame, FootballGame};
+use crate::generic::GenericGame;
 use crate::golf::Golf;
 use crate::hockey::HockeyGame;
+use crate::motorsport::MotorsportEvent;
+use crate::soccer::SoccerGame;
+use crate::tennis::TennisGame;
 
 use crate::animation;
+use crate::config;
+use crate::diagnostics::NetworkUsage;
 use crate::game;
 use crate::matrix;
+use crate::recap::{self, RecapStore};
 use crate::scheduler;
+use crate::standings::{self, LeagueStandings};
+use crate::score_events::{self, ScoreEvent, ScoreEventKind};
+use crate::score_source::{self, CacheValidators, FetchError, Fetched, ScoreSource};
+use crate::split_view;
 use std::any::Any;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::{mpsc, Arc};
 
+use chrono::{DateTime, TimeZone, Utc};
 use rand::seq::SliceRandom;
-use serde::Deserialize;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
-const DORMANT_REFRESH_TIME: Duration = Duration::from_secs(60 * 5); // 5 minutes
-const ACTIVE_REFRESH_TIME: Duration = Duration::from_secs(60); // 1 minute
+pub const SCORE_CACHE_PATH: &str = "score_cache.json";
+const MAX_CACHE_AGE_HOURS: i64 = 6; // Older cached games aren't worth showing
+const CACHE_WRITE_INTERVAL: Duration = Duration::from_secs(60 * 10); // Spares the SD card
+
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type")]
 enum SportData {
@@ -30,8 +51,29 @@ enum SportData {
     CollegeFootball(CollegeFootballGame),
     Football(FootballGame),
     Golf(Golf),
+    Soccer(SoccerGame),
+    Tennis(TennisGame),
+    Motorsport(MotorsportEvent),
+    Cricket(CricketGame),
+    #[serde(skip)]
+    Generic(GenericGame), // Built by parse_game for unknown types
 }
 
+// The `type` tags SportData knows how to deserialize
+const SPORT_TYPES: [&str; 11] = [
+    "Hockey",
+    "Baseball",
+    "CollegeBasketball",
+    "Basketball",
+    "CollegeFootball",
+    "Football",
+    "Golf",
+    "Soccer",
+    "Tennis",
+    "Motorsport",
+    "Cricket",
+];
+
 impl SportData {
     fn get_inner(&self) -> &(dyn game::Sport) {
         match self {
@@ -42,8 +84,18 @@ impl SportData {
             SportData::CollegeFootball(college_football) => college_football,
             SportData::Football(football) => football,
             SportData::Golf(golf) => golf,
+            SportData::Soccer(soccer) => soccer,
+            SportData::Tennis(tennis) => tennis,
+            SportData::Motorsport(motorsport) => motorsport,
+            SportData::Cricket(cricket) => cricket,
+            SportData::Generic(generic) => generic,
         }
     }
+
+    fn get_ref(&self) -> GameRef {
+        let (sport_id, id) = self.get_inner().get_game_key();
+        GameRef { sport_id, id }
+    }
 }
 
 struct AWSData {
@@ -52,6 +104,10 @@ struct AWSData {
     data_received_timestamp: Instant,
     last_cycle_timestamp: Option<Instant>,
     active_index: Option<usize>,
+    stale_since: Option<DateTime<Utc>>, // Set when the data was loaded from the score cache
+    standings: Vec<LeagueStandings>,
+    upcoming: Vec<game::CommonGameData>, // Games past the ones in `games`, for the countdown
+    recap: Vec<game::CommonGameData>,    // Recently finished games, including ones no longer in `games`
 }
 
 impl AWSData {
@@ -62,6 +118,10 @@ impl AWSData {
             data_received_timestamp: Instant::now(),
             last_cycle_timestamp: None,
             active_index: None,
+            stale_since: None,
+            standings: Vec::new(),
+            upcoming: Vec::new(),
+            recap: Vec::new(),
         }
     }
 
@@ -69,17 +129,35 @@ impl AWSData {
         &mut self,
         new_data: AWSData,
         current_leagues: &HashSet<common::ScreenId>,
-        favorite_teams: &[common::FavoriteTeam],
-    ) {
+        settings: &common::ScoreboardSettingsData,
+    ) -> Vec<ScoreEvent> {
+        let events = self.diff(&new_data.games);
         self.games = new_data.games;
-        self.filter_games(current_leagues, favorite_teams);
+        self.filter_games(current_leagues, settings);
         self.data_received_timestamp = new_data.data_received_timestamp;
+        self.stale_since = new_data.stale_since;
+        self.standings = new_data.standings;
+        self.upcoming = new_data.upcoming;
+        self.recap = new_data.recap;
+        events
+    }
+
+    // What changed between the games on screen and `new_games`
+    fn diff(&self, new_games: &[SportData]) -> Vec<ScoreEvent> {
+        if self.stale_since.is_some() {
+            // Anything that happened while the board was offline is old news
+            return Vec::new();
+        }
+        score_events::diff_games(
+            self.games.iter().map(SportData::get_inner),
+            new_games.iter().map(SportData::get_inner),
+        )
     }
 
     pub fn filter_games(
         &mut self,
         current_leagues: &HashSet<common::ScreenId>,
-        favorite_teams: &[common::FavoriteTeam],
+        settings: &common::ScoreboardSettingsData,
     ) {
         self.filtered_games = {
             let (priority_games, other_games): (Vec<usize>, Vec<usize>) = self
@@ -89,27 +167,51 @@ impl AWSData {
                 .filter(|(_, game)| current_leagues.contains(&game.get_inner().get_screen_id()))
                 .map(|(i, _)| i)
                 .partition(|i| {
-                    favorite_teams.iter().any(|favorite_team| {
-                        self.games[*i].get_inner().get_screen_id() == favorite_team.screen_id
-                            && self.games[*i]
-                                .get_inner()
-                                .involves_team(favorite_team.team_id)
-                            && self.games[*i].get_inner().should_focus()
-                    })
+                    let game = self.games[*i].get_inner();
+                    game.should_focus()
+                        && settings
+                            .get_focus_teams(game.get_screen_id())
+                            .iter()
+                            .any(|team_id| game.involves_team(*team_id))
                 });
 
             if !priority_games.is_empty() {
-                priority_games
+                // Leagues set to always rotate keep their games in the rotation alongside focused games
+                let mut games: Vec<usize> = priority_games
+                    .into_iter()
+                    .chain(other_games.into_iter().filter(|i| {
+                        settings
+                            .get_screen_settings(self.games[*i].get_inner().get_screen_id())
+                            .map_or(false, |screen| screen.always_rotate)
+                    }))
+                    .collect();
+                games.sort_unstable();
+                games
             } else {
                 other_games
             }
         };
+        if settings.uses_split_view() {
+            let games = &self.games;
+            self.filtered_games
+                .sort_by_key(|i| split_view::get_preference(games[*i].get_inner().get_common()));
+        }
 
         info!("Filtered games: {:?}", self.filtered_games);
     }
 
-    pub fn try_rotate(&mut self, rotation_time: Duration) {
+    // Rotates a page of `games_per_page` games at a time
+    pub fn try_rotate(&mut self, settings: &common::ScoreboardSettingsData, games_per_page: usize) {
         let now = Instant::now();
+        let rotation_time = match self
+            .active_index
+            .and_then(|index| self.filtered_games.get(index))
+        {
+            Some(game_index) => {
+                settings.get_rotation_time(self.games[*game_index].get_inner().get_screen_id())
+            }
+            None => settings.rotation_time,
+        };
         self.active_index = match self.filtered_games.len() {
             0 => None,
             games_length => {
@@ -123,7 +225,13 @@ impl AWSData {
                 if should_rotate {
                     self.last_cycle_timestamp = Some(now);
                     let new_index = match self.active_index {
-                        Some(index) => Some((index + 1) % games_length),
+                        Some(index) => {
+                            let next_page = (index / games_per_page + 1) * games_per_page;
+                            match next_page < games_length {
+                                true => Some(next_page),
+                                false => Some(0), // Back to the first page
+                            }
+                        }
                         None => Some(0),
                     };
                     info!(
@@ -138,6 +246,93 @@ impl AWSData {
         };
     }
 
+    pub fn merge(
+        &mut self,
+        delta: GameDelta,
+        current_leagues: &HashSet<common::ScreenId>,
+        settings: &common::ScoreboardSettingsData,
+    ) -> Vec<ScoreEvent> {
+        let GameDelta { games, removed } = delta;
+        let events = self.diff(&games);
+        for game in games {
+            let game_ref = game.get_ref();
+            match self
+                .games
+                .iter()
+                .position(|existing| existing.get_ref() == game_ref)
+            {
+                Some(index) => self.games[index] = game,
+                None => self.games.push(game),
+            }
+        }
+        self.games.retain(|game| !removed.contains(&game.get_ref()));
+        self.filter_games(current_leagues, settings);
+        self.data_received_timestamp = Instant::now();
+        events
+    }
+
+    // Games loaded from the score cache stay on screen through fetch errors until they are too old
+    fn is_usable_cache(&self) -> bool {
+        self.stale_since.map_or(false, |stale_since| {
+            Utc::now().signed_duration_since(stale_since)
+                < chrono::Duration::hours(MAX_CACHE_AGE_HOURS)
+        })
+    }
+
+    // Polling slows down while streaming, so the open stream keeps the games from looking out of
+    // date. Cached games stay marked until a fetch or delta replaces them
+    fn mark_streaming(&mut self) {
+        if self.stale_since.is_none() {
+            self.data_received_timestamp = Instant::now();
+        }
+    }
+
+    // The source confirmed these games are still current
+    pub fn mark_current(&mut self) {
+        self.data_received_timestamp = Instant::now();
+        self.stale_since = None;
+    }
+
+    fn find_game(&self, game_ref: GameRef) -> Option<&SportData> {
+        self.games.iter().find(|game| game.get_ref() == game_ref)
+    }
+
+    // Puts a game on screen and restarts its rotation time
+    fn show_game(&mut self, game_ref: GameRef) {
+        let games = &self.games;
+        if let Some(index) = self
+            .filtered_games
+            .iter()
+            .position(|i| games[*i].get_ref() == game_ref)
+        {
+            self.active_index = Some(index);
+            self.last_cycle_timestamp = Some(Instant::now());
+        }
+    }
+
+    fn get_next_games(&self, favorite_teams: &[common::FavoriteTeam]) -> Vec<NextGame<'_>> {
+        let games: Vec<&game::CommonGameData> = self
+            .games
+            .iter()
+            .map(|game| game.get_inner().get_common())
+            .chain(self.upcoming.iter())
+            .collect();
+        countdown::get_next_games(&games, favorite_teams)
+    }
+
+    // The games on the same page as the active game
+    fn get_page(&self, games_per_page: usize) -> Vec<&SportData> {
+        let start = self
+            .active_index
+            .map_or(0, |index| index - index % games_per_page);
+        self.filtered_games
+            .iter()
+            .skip(start)
+            .take(games_per_page)
+            .map(|i| &self.games[*i])
+            .collect()
+    }
+
     pub fn get_active_game(&self) -> Option<&SportData> {
         self.active_index
             .map(|index| &self.games[self.filtered_games[index]])
@@ -146,7 +341,7 @@ impl AWSData {
 
 enum ReceivedData {
     Valid(AWSData, u8),
-    Error,
+    Error(FetchError),
     None,
 }
 pub struct AWSScreen {
@@ -154,40 +349,98 @@ pub struct AWSScreen {
     current_leagues: HashSet<common::ScreenId>,
     data: ReceivedData,
     settings: Arc<common::ScoreboardSettingsData>,
-    data_pipe_receiver: mpsc::Receiver<Result<AWSData, String>>,
+    data_pipe_receiver: mpsc::Receiver<RefreshUpdate>,
     refresh_control_sender: mpsc::Sender<RefreshThreadState>,
     loading_animation: animation::WavesAnimation,
     fonts: matrix::FontBook,
     pixels: matrix::PixelBook,
     flavor_text: Option<String>,
+    celebration: Option<Celebration>,
+    last_celebrations: HashMap<(common::ScreenId, u32), Instant>, // By sport and team, for the cooldown
+    games_per_page: usize,
+}
+
+enum RefreshUpdate {
+    Data(AWSData),
+    Delta(GameDelta), // Changed games from the score stream
+    NotModified,      // The games haven't changed since the last update
+    StreamAlive,      // The score stream is connected, so no delta means nothing changed
+    Error(FetchError),
+}
+
+// Identifies a game across sports, game ids are only unique within a sport. The raw sport_id
+// keeps games of sports we don't have a screen for apart
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+struct GameRef {
+    sport_id: u16,
+    id: usize,
+}
+
+// One event from the score stream: games to add or replace, and games that are gone
+#[derive(Deserialize, Debug)]
+struct GameDelta {
+    #[serde(default, deserialize_with = "deserialize_games")]
+    games: Vec<SportData>,
+    #[serde(default)]
+    removed: Vec<GameRef>,
+}
+
+#[derive(Debug)]
+enum StreamEvent {
+    Connected,
+    Keepalive,
+    Delta(GameDelta),
+}
+
+// Files the refresh thread keeps in root_path
+pub struct RefreshFiles {
+    pub score_cache: PathBuf,
+    pub network_usage: PathBuf,
+    pub recap: PathBuf,
 }
 
 enum RefreshThreadState {
     Active,
     Hibernating,
+    StreamConnected, // Deltas are arriving, polling only needs to resync occasionally
+    StreamDropped,
 }
 
 impl AWSScreen {
     pub fn new(
         sender: mpsc::Sender<scheduler::DelayedCommand>,
-        base_url: String,
-        api_key: String,
+        source: Box<dyn ScoreSource>,
+        refresh: config::RefreshConfig,
+        files: RefreshFiles,
         settings: Arc<common::ScoreboardSettingsData>,
         fonts: matrix::FontBook,
         pixels: matrix::PixelBook,
-        matrix_mode: matrix::MatrixMode,
     ) -> AWSScreen {
         let (data_pipe_sender, data_pipe_receiver) = mpsc::channel();
 
         let (refresh_control_sender, refresh_control_receiver) = mpsc::channel();
 
+        if let Some(stream_url) = refresh.stream_url.clone() {
+            let stream_data_sender = data_pipe_sender.clone();
+            let stream_control_sender = refresh_control_sender.clone();
+            let stream_refresh = refresh.clone();
+            let _stream_thread = std::thread::spawn(move || {
+                AWSScreen::run_stream_thread(
+                    stream_url,
+                    stream_refresh,
+                    stream_data_sender,
+                    stream_control_sender,
+                )
+            });
+        }
+
         let _refresh_thread = std::thread::spawn(move || {
             AWSScreen::run_refresh_thread(
-                base_url,
+                source,
                 refresh_control_receiver,
-                api_key,
+                refresh,
+                files,
                 data_pipe_sender,
-                matrix_mode,
             )
         });
         AWSScreen {
@@ -201,6 +454,9 @@ impl AWSScreen {
             fonts,
             pixels,
             flavor_text: None,
+            celebration: None,
+            last_celebrations: HashMap::new(),
+            games_per_page: 1,
         }
     }
 
@@ -236,25 +492,109 @@ impl AWSScreen {
 
         self.loading_animation.draw(canvas);
     }
-    fn draw_error(&self, canvas: &mut rpi_led_matrix::LedCanvas) {
+    fn draw_error(&self, canvas: &mut rpi_led_matrix::LedCanvas, error: &FetchError) {
         let font = &self.fonts.font4x6;
         let red = common::new_color(255, 0, 0);
+        let white = common::new_color(255, 255, 255);
+        let (title, lines) = match error {
+            FetchError::InvalidApiKey => {
+                ("Invalid API Key", vec!["Check the key in", "secrets.txt"])
+            }
+            _ => ("Connection Error", vec![]),
+        };
         canvas.draw_text(
             &font.led_font,
-            "Connection Error",
+            title,
             1,
             1 + font.dimensions.height,
             &red,
             0,
             false,
         );
+        let detail = error.describe();
+        let lines = match lines.is_empty() {
+            true => vec![detail.as_str()],
+            false => lines,
+        };
+        for (i, line) in lines.iter().enumerate() {
+            canvas.draw_text(
+                &font.led_font,
+                line,
+                1,
+                1 + (2 + i as i32) * (font.dimensions.height + 1),
+                &white,
+                0,
+                false,
+            );
+        }
+    }
+    fn draw_countdown(&self, canvas: &mut rpi_led_matrix::LedCanvas, data: &AWSData) {
+        let font = &self.fonts.font4x6;
+        let next_games = data.get_next_games(&self.settings.favorite_teams);
+        if !countdown::draw_countdown(canvas, font, &next_games, &self.settings.timezone) {
+            let white = common::new_color(255, 255, 255);
+            canvas.draw_text(
+                &font.led_font,
+                "No upcoming games",
+                2,
+                2 + font.dimensions.height,
+                &white,
+                0,
+                false,
+            );
+        }
     }
-    fn draw_no_games(&self, canvas: &mut rpi_led_matrix::LedCanvas) {
+
+    fn draw_recap(&self, canvas: &mut rpi_led_matrix::LedCanvas, data: &AWSData) {
+        let font = &self.fonts.font4x6;
+        let games = recap::get_recap_games(&data.recap, &self.settings);
+        if !recap::draw_recap(canvas, font, &games, &self.settings) {
+            let white = common::new_color(255, 255, 255);
+            canvas.draw_text(
+                &font.led_font,
+                "No games yesterday",
+                2,
+                2 + font.dimensions.height,
+                &white,
+                0,
+                false,
+            );
+        }
+    }
+
+    fn draw_split_view(&self, canvas: &mut rpi_led_matrix::LedCanvas, data: &AWSData) {
+        let tiles = split_view::get_tiles(canvas.canvas_size());
+        for (game, tile) in data.get_page(tiles.len()).into_iter().zip(tiles) {
+            split_view::draw_mini_scoreboard(
+                canvas,
+                &self.fonts.font4x6,
+                game.get_inner().get_common(),
+                &self.settings.timezone,
+                tile,
+            );
+        }
+    }
+
+    // Standings fill in for days without games
+    fn draw_no_games(&self, canvas: &mut rpi_led_matrix::LedCanvas, data: &AWSData) {
         let font = &self.fonts.font4x6;
+        if standings::draw_standings(
+            canvas,
+            font,
+            &data.standings,
+            &self.current_leagues,
+            &self.settings,
+        ) {
+            return;
+        }
         let white = common::new_color(255, 255, 255);
+        let message = match self.settings.active_screen {
+            common::ScreenId::Standings => "No standings yet",
+            _ => "No events today",
+        };
         canvas.draw_text(
             &font.led_font,
-            "No events today",
+            message,
             2,
             2 + font.dimensions.height,
             &white,
@@ -262,119 +602,441 @@ impl AWSScreen {
             false,
         );
     }
+    // Everything that reacts to games changing hooks in here
+    fn handle_score_events(&mut self, events: Vec<ScoreEvent>) {
+        for event in &events {
+            info!("Score event: {:?}", event);
+        }
+        self.start_celebration(&events);
+    }
+
+    // Interrupts the rotation when a favorite team scores, unless one is already playing
+    fn start_celebration(&mut self, events: &[ScoreEvent]) {
+        let data = match &mut self.data {
+            ReceivedData::Valid(data, _error_count) => data,
+            _ => return,
+        };
+        for event in events {
+            let team_id = match event.kind {
+                ScoreEventKind::ScoreChanged { team_id, .. } => team_id,
+                _ => continue,
+            };
+            if self.celebration.is_some()
+                || !self.current_leagues.contains(&event.sport_id)
+                || !self.settings.should_celebrate(event.sport_id, team_id)
+            {
+                continue;
+            }
+            if let Some(last_celebration) = self.last_celebrations.get(&(event.sport_id, team_id)) {
+                if last_celebration.elapsed() < CELEBRATION_COOLDOWN {
+                    info!("Skipping celebration for team {}, cooling down", team_id);
+                    continue;
+                }
+            }
+            let game_ref = GameRef {
+                sport_id: event.sport_id as u16,
+                id: event.game_id,
+            };
+            let game = match data.find_game(game_ref) {
+                Some(game) => game.get_inner(),
+                None => continue,
+            };
+            let walk_off = game.get_common().home_team.id == team_id
+                && events.iter().any(|other| {
+                    (other.sport_id, other.game_id) == (event.sport_id, event.game_id)
+                        && other.kind == ScoreEventKind::GameFinal
+                });
+            if let Some(celebration) = Celebration::for_event(game, event, walk_off) {
+                info!("Celebrating a score by team {}", team_id);
+                self.last_celebrations
+                    .insert((event.sport_id, team_id), Instant::now());
+                self.celebration = Some(celebration);
+                data.show_game(game_ref);
+            }
+        }
+    }
+
     fn process(&mut self) {
-        if let Ok(data_or_error) = self.data_pipe_receiver.try_recv() {
-            match data_or_error {
-                Ok(mut new_data) => match &mut self.data {
+        if let Ok(update) = self.data_pipe_receiver.try_recv() {
+            match update {
+                RefreshUpdate::Data(mut new_data) => match &mut self.data {
                     ReceivedData::Valid(current_data, error_count) => {
-                        current_data.update(
-                            new_data,
-                            &self.current_leagues,
-                            &self.settings.favorite_teams,
-                        );
-                        current_data.try_rotate(self.settings.rotation_time);
+                        let events =
+                            current_data.update(new_data, &self.current_leagues, &self.settings);
+                        current_data.try_rotate(&self.settings, self.games_per_page);
                         *error_count = 0;
+                        self.handle_score_events(events);
                     }
                     _ => {
-                        new_data.filter_games(&self.current_leagues, &self.settings.favorite_teams);
+                        new_data.filter_games(&self.current_leagues, &self.settings);
                         self.data = ReceivedData::Valid(new_data, 0);
                     }
                 },
-                Err(e) => {
+                RefreshUpdate::Delta(delta) => {
+                    // Deltas only make sense on top of a full set of games
+                    if let ReceivedData::Valid(current_data, _error_count) = &mut self.data {
+                        let events =
+                            current_data.merge(delta, &self.current_leagues, &self.settings);
+                        self.handle_score_events(events);
+                    }
+                }
+                RefreshUpdate::NotModified => {
+                    if let ReceivedData::Valid(current_data, error_count) = &mut self.data {
+                        current_data.mark_current();
+                        *error_count = 0;
+                    }
+                }
+                RefreshUpdate::StreamAlive => {
+                    if let ReceivedData::Valid(current_data, _error_count) = &mut self.data {
+                        current_data.mark_streaming();
+                    }
+                }
+                RefreshUpdate::Error(e) => {
                     info!("Received error: {}", e);
                     match &mut self.data {
-                        ReceivedData::Valid(_, error_count) => {
+                        _ if e.is_fatal() => self.data = ReceivedData::Error(e),
+                        ReceivedData::Valid(data, error_count) => {
                             *error_count += 1;
                             info!("Error count is {}", *error_count);
-                            if *error_count > 5 {
-                                self.data = ReceivedData::Error
+                            if data.is_usable_cache() {
+                                info!("Still showing cached games");
+                            } else if *error_count > 5 {
+                                self.data = ReceivedData::Error(e)
                             }
                         }
-                        _ => self.data = ReceivedData::Error,
+                        _ => self.data = ReceivedData::Error(e),
                     }
                 }
             }
             self.flavor_text = None; // Clear the flavor text
         }
 
+        // Hold the rotation while celebrating, then give the scoring game its full rotation time
+        if let Some(celebration) = &self.celebration {
+            if !celebration.is_finished() {
+                return;
+            }
+            let game_ref = GameRef {
+                sport_id: celebration.sport_id as u16,
+                id: celebration.game_id,
+            };
+            self.celebration = None;
+            if let ReceivedData::Valid(current_data, _error_count) = &mut self.data {
+                current_data.show_game(game_ref);
+            }
+        }
+
         // if we need to change the displayed image, do that now
         if let ReceivedData::Valid(current_data, _error_count) = &mut self.data {
-            current_data.try_rotate(self.settings.rotation_time);
+            current_data.try_rotate(&self.settings, self.games_per_page);
         }
     }
 
     fn run_refresh_thread(
-        base_url: String,
+        mut source: Box<dyn ScoreSource>,
         refresh_control_receiver: mpsc::Receiver<RefreshThreadState>,
-        api_key: String,
-        data_sender: mpsc::Sender<Result<AWSData, String>>,
-        matrix_mode: matrix::MatrixMode,
+        refresh: config::RefreshConfig,
+        files: RefreshFiles,
+        data_sender: mpsc::Sender<RefreshUpdate>,
     ) {
-        let mut wait_time = DORMANT_REFRESH_TIME;
+        // Show the last good games while the first fetch is in flight
+        let mut recap = RecapStore::read(&files.recap);
+        let mut validators = match read_score_cache(&files.score_cache) {
+            Some((mut cached_data, validators)) => {
+                cached_data.recap = recap.get_games();
+                data_sender.send(RefreshUpdate::Data(cached_data)).unwrap();
+                validators
+            }
+            None => CacheValidators::default(),
+        };
+        let mut network_usage = NetworkUsage::read(&files.network_usage);
+        let mut last_usage_write: Option<Instant> = None;
+        let mut last_cache_write: Option<(Instant, String)> = None; // When, and the body written
+        let mut active = false;
+        let mut streaming = false;
         let mut skip_flag = false;
+        let mut failures: u32 = 0;
         loop {
             if !skip_flag {
-                match matrix_mode {
-                    matrix::MatrixMode::Production => {
-                        info!("Fetching games from {}", &base_url);
-                        let resp = game::fetch_games(&base_url, "all_v4", &api_key);
-                        if resp.error() {
-                            error!("There was an error fetching games for endpoint",);
-                            data_sender.send(Err("Network Error".to_owned())).unwrap();
-                        } else {
-                            info!("{:#?}", resp);
-                            match resp.into_string() {
-                                Ok(resp_string) => {
-                                    process_data_string(&resp_string, &data_sender);
-                                }
-                                Err(e) => {
-                                    error!("Failed to convert response into a string {:?}", e);
-                                    data_sender
-                                        .send(Err("Invalid Response".to_string()))
-                                        .unwrap();
+                info!("Fetching games from {}", source.describe());
+                let update = match source.fetch(&mut validators) {
+                    Ok(Fetched::Updated { body, bytes }) => {
+                        network_usage.record(bytes, false);
+                        process_data_string(&body).map(|mut data| {
+                            let cache_write_due = match &last_cache_write {
+                                Some((written_at, written_body)) => {
+                                    written_at.elapsed() >= CACHE_WRITE_INTERVAL
+                                        && *written_body != body
                                 }
+                                None => true,
+                            };
+                            if cache_write_due {
+                                write_score_cache(&files.score_cache, &body, &validators);
+                                last_cache_write = Some((Instant::now(), body.clone()));
                             }
-                        }
+                            if recap.record(&body) {
+                                recap.write(&files.recap);
+                            }
+                            data.recap = recap.get_games();
+                            RefreshUpdate::Data(data)
+                        })
+                    }
+                    Ok(Fetched::NotModified) => {
+                        network_usage.record(0, true);
+                        Ok(RefreshUpdate::NotModified)
+                    }
+                    Err(e) => Err(e),
+                };
+                if last_usage_write.map_or(true, |written_at| {
+                    written_at.elapsed() >= CACHE_WRITE_INTERVAL
+                }) {
+                    network_usage.write(&files.network_usage);
+                    last_usage_write = Some(Instant::now());
+                }
+                match update {
+                    Ok(update) => {
+                        failures = 0;
+                        data_sender.send(update).unwrap();
                     }
-                    matrix::MatrixMode::Demo => {
-                        info!("Loading demo games");
-                        let data_string = include_str!("../assets/demo/demo_scores.json");
-                        process_data_string(data_string, &data_sender);
+                    Err(e) => {
+                        failures += 1;
+                        // The screen may drop its games after errors, so ask for the full payload next time
+                        validators = CacheValidators::default();
+                        data_sender.send(RefreshUpdate::Error(e)).unwrap();
                     }
                 }
             }
             skip_flag = false;
-            if let Ok(state) = refresh_control_receiver.recv_timeout(wait_time) {
+            let delay = match failures {
+                0 if active && !streaming => refresh.active,
+                0 => refresh.dormant,
+                _ => {
+                    let delay = backoff_delay(&refresh, failures);
+                    info!("Fetch failed {} times, retrying in {:?}", failures, delay);
+                    delay
+                }
+            };
+            if let Ok(state) = refresh_control_receiver.recv_timeout(delay) {
                 match state {
                     RefreshThreadState::Active => {
-                        wait_time = ACTIVE_REFRESH_TIME;
+                        active = true;
                     }
                     RefreshThreadState::Hibernating => {
-                        wait_time = DORMANT_REFRESH_TIME;
+                        active = false;
                         skip_flag = true;
                     }
+                    RefreshThreadState::StreamConnected => {
+                        streaming = true;
+                        skip_flag = true;
+                    }
+                    RefreshThreadState::StreamDropped => {
+                        streaming = false; // Fetch right away to catch anything the stream missed
+                    }
                 }
             }
         }
     }
+
+    fn run_stream_thread(
+        url: String,
+        refresh: config::RefreshConfig,
+        data_sender: mpsc::Sender<RefreshUpdate>,
+        refresh_control_sender: mpsc::Sender<RefreshThreadState>,
+    ) {
+        let mut failures: u32 = 0;
+        loop {
+            info!("Connecting to score stream at {}", url);
+            let mut connected = false;
+            let result = read_event_stream(&url, |event| match event {
+                StreamEvent::Connected => {
+                    connected = true;
+                    refresh_control_sender
+                        .send(RefreshThreadState::StreamConnected)
+                        .unwrap();
+                    data_sender.send(RefreshUpdate::StreamAlive).unwrap();
+                }
+                StreamEvent::Keepalive => data_sender.send(RefreshUpdate::StreamAlive).unwrap(),
+                StreamEvent::Delta(delta) => data_sender.send(RefreshUpdate::Delta(delta)).unwrap(),
+            });
+            if connected {
+                failures = 0;
+                refresh_control_sender
+                    .send(RefreshThreadState::StreamDropped)
+                    .unwrap();
+            }
+            if let Err(e) = result {
+                error!("Score stream failed: {}", e);
+            }
+            failures += 1;
+            let delay = backoff_delay(&refresh, failures);
+            info!("Score stream closed, reconnecting in {:?}", delay);
+            std::thread::sleep(delay);
+        }
+    }
 }
 
-fn process_data_string(resp_string: &str, data_sender: &mpsc::Sender<Result<AWSData, String>>) {
-    let result: Result<game::Response<SportData>, _> = serde_json::from_str(resp_string);
-    if let Ok(response) = result {
-        info!("Successfully parsed response",);
-        data_sender
-            .send(Ok(AWSData::new(response.data.games)))
-            .unwrap();
-    } else {
-        error!(
-            "Failed to parse response {}, reason: {}",
-            resp_string,
-            result.err().unwrap()
-        );
-        data_sender.send(Err("Invalid Data".to_owned())).unwrap();
+// Reads a server-sent events feed of game deltas until the server closes it
+fn read_event_stream(url: &str, mut on_event: impl FnMut(StreamEvent)) -> Result<(), FetchError> {
+    let resp = ureq::get(url)
+        .set("Accept", "text/event-stream")
+        .timeout_connect(10_000)
+        .timeout_read(90_000) // Servers are expected to send keepalive comments more often than this
+        .call();
+    if resp.error() {
+        return Err(score_source::classify_response(&resp, false));
+    }
+    on_event(StreamEvent::Connected);
+
+    let mut data = String::new();
+    for line in BufReader::new(resp.into_reader()).lines() {
+        let line = line.map_err(|e| score_source::classify_io_error(&e))?;
+        if line.is_empty() {
+            // A blank line ends the event
+            if !data.is_empty() {
+                match serde_json::from_str::<GameDelta>(&data) {
+                    Ok(delta) => on_event(StreamEvent::Delta(delta)),
+                    Err(e) => error!("Failed to parse stream event {}, reason: {}", data, e),
+                }
+                data.clear();
+            }
+        } else if let Some(value) = line.strip_prefix("data:") {
+            if !data.is_empty() {
+                data.push('\n');
+            }
+            data.push_str(value.trim_start());
+        } else if line.starts_with(':') {
+            on_event(StreamEvent::Keepalive);
+        }
+        // Event names and ids are ignored
+    }
+    Ok(())
+}
+
+// Exponential backoff with jitter, so a fleet of boards doesn't retry in lockstep
+fn backoff_delay(refresh: &config::RefreshConfig, failures: u32) -> Duration {
+    let exponent = std::cmp::min(failures.saturating_sub(1), 16);
+    let delay = std::cmp::min(
+        refresh.retry_initial * 2u32.pow(exponent),
+        refresh.retry_max,
+    );
+    delay.mul_f64(rand::thread_rng().gen_range(0.5, 1.0))
+}
+
+// Parse one game, falling back to a plain scoreboard for sport types we don't know yet
+fn parse_game(mut value: serde_json::Value) -> Result<SportData, String> {
+    let sport_type = value["type"].as_str().unwrap_or_default().to_string();
+    if SPORT_TYPES.contains(&sport_type.as_str()) {
+        return serde_json::from_value(value).map_err(|e| format!("{} game: {}", sport_type, e));
+    }
+    let mut common = value["common"].take();
+    let sport_id = common["sport_id"]
+        .as_u64()
+        .unwrap_or(common::ScreenId::Smart as u64) as u16;
+    let result = serde_json::from_value(common.clone()).or_else(|_| {
+        // A new sport will usually come with a sport_id we don't have a screen for
+        common["sport_id"] = serde_json::json!(common::ScreenId::Smart as u16);
+        serde_json::from_value(common)
+    });
+    match result {
+        Ok(common) => {
+            info!(
+                "Showing unknown sport type {:?} as a generic game",
+                sport_type
+            );
+            Ok(SportData::Generic(GenericGame { common, sport_id }))
+        }
+        Err(e) => Err(format!("Unknown sport type {:?}: {}", sport_type, e)),
+    }
+}
+
+// Parse games independently so one bad game doesn't take the rest down with it
+fn parse_games(values: Vec<serde_json::Value>) -> Vec<SportData> {
+    values
+        .into_iter()
+        .filter_map(|value| match parse_game(value) {
+            Ok(game) => Some(game),
+            Err(e) => {
+                error!("Skipping game that failed to parse: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+fn deserialize_games<'de, D>(deserializer: D) -> Result<Vec<SportData>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values: Vec<serde_json::Value> = Deserialize::deserialize(deserializer)?;
+    Ok(parse_games(values))
+}
+
+fn process_data_string(resp_string: &str) -> Result<AWSData, FetchError> {
+    let result: Result<game::Response<serde_json::Value>, _> = serde_json::from_str(resp_string);
+    match result {
+        Ok(response) => {
+            info!("Successfully parsed response",);
+            let mut data = AWSData::new(parse_games(response.data.games));
+            data.standings = standings::parse_standings(response.data.standings);
+            data.upcoming = countdown::parse_upcoming(response.data.upcoming);
+            Ok(data)
+        }
+        Err(e) => {
+            error!("Failed to parse response {}, reason: {}", resp_string, e);
+            Err(FetchError::Parse)
+        }
     }
 }
+
+// The last response that parsed, kept so the board has something to show after a reboot
+#[derive(Deserialize, Serialize)]
+struct ScoreCache {
+    timestamp: i64, // Unix seconds
+    response: String,
+    #[serde(default)]
+    validators: CacheValidators,
+}
+
+fn write_score_cache(cache_path: &Path, resp_string: &str, validators: &CacheValidators) {
+    let cache = ScoreCache {
+        timestamp: Utc::now().timestamp(),
+        response: resp_string.to_string(),
+        validators: validators.clone(),
+    };
+    let result = serde_json::to_string(&cache)
+        .map_err(|e| e.to_string())
+        .and_then(|cache_string| fs::write(cache_path, cache_string).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        error!("Failed to write score cache to {:?}: {}", cache_path, e);
+    }
+}
+
+fn read_score_cache(cache_path: &Path) -> Option<(AWSData, CacheValidators)> {
+    let cache_string = fs::read_to_string(cache_path).ok()?;
+    let cache: ScoreCache = match serde_json::from_str(&cache_string) {
+        Ok(cache) => cache,
+        Err(e) => {
+            error!("Failed to parse score cache {:?}: {}", cache_path, e);
+            return None;
+        }
+    };
+    if Utc::now().timestamp() - cache.timestamp > MAX_CACHE_AGE_HOURS * 60 * 60 {
+        info!("Ignoring cached games from {}, too old", cache.timestamp);
+        return None;
+    }
+    let mut data = process_data_string(&cache.response).ok()?;
+    info!("Loaded cached games from {}", cache.timestamp);
+    data.stale_since = Some(Utc.timestamp(cache.timestamp, 0));
+    Some((data, cache.validators))
+}
+
+// Amber corner so cached games can't be mistaken for live ones
+fn draw_stale_marker(canvas: &mut rpi_led_matrix::LedCanvas) {
+    let (width, height) = canvas.canvas_size();
+    let amber = common::new_color(255, 140, 0);
+    matrix::draw_rectangle(canvas, (width - 3, height - 2), (width - 1, height), &amber);
+}
+
 impl matrix::ScreenProvider for AWSScreen {
     fn activate(&mut self) {
         info!("Activating screen");
@@ -400,7 +1062,11 @@ impl matrix::ScreenProvider for AWSScreen {
             | common::ScreenId::CollegeFootball
             | common::ScreenId::Basketball
             | common::ScreenId::Football
-            | common::ScreenId::Golf => (vec![self.settings.active_screen]).into_iter().collect(),
+            | common::ScreenId::Golf
+            | common::ScreenId::Soccer
+            | common::ScreenId::Tennis
+            | common::ScreenId::Motorsport
+            | common::ScreenId::Cricket => (vec![self.settings.active_screen]).into_iter().collect(),
             _ => (vec![
                 common::ScreenId::Hockey,
                 common::ScreenId::Baseball,
@@ -409,28 +1075,66 @@ impl matrix::ScreenProvider for AWSScreen {
                 common::ScreenId::CollegeFootball,
                 common::ScreenId::Football,
                 common::ScreenId::Golf,
+                common::ScreenId::Soccer,
+                common::ScreenId::Tennis,
+                common::ScreenId::Motorsport,
+                common::ScreenId::Cricket,
+                common::ScreenId::Smart, // Generic games for sports we don't know about yet
             ])
             .into_iter()
             .collect(),
         };
         if let ReceivedData::Valid(data, _error_count) = &mut self.data {
-            data.filter_games(&self.current_leagues, &self.settings.favorite_teams);
-            data.try_rotate(self.settings.rotation_time);
+            data.filter_games(&self.current_leagues, &self.settings);
+            data.try_rotate(&self.settings, self.games_per_page);
         }
     }
 
     fn draw(&mut self, canvas: &mut rpi_led_matrix::LedCanvas) {
+        self.games_per_page = match self.settings.uses_split_view() {
+            true => split_view::get_tiles(canvas.canvas_size()).len(),
+            false => 1,
+        };
         // Check if there is any new data. If there is, copy it in
         self.process();
         let now = Instant::now();
         // Actually draw the data
         match &self.data {
             ReceivedData::Valid(current_data, _error_count) => {
-                if now.duration_since(current_data.data_received_timestamp)
-                    < Duration::from_secs(60 * 5)
+                if current_data.is_usable_cache()
+                    || now.duration_since(current_data.data_received_timestamp)
+                        < Duration::from_secs(60 * 5)
                 {
-                    match current_data.get_active_game() {
-                        Some(active_game) => {
+                    match (
+                        &self.celebration,
+                        self.settings.active_screen,
+                        current_data.get_active_game(),
+                    ) {
+                        (Some(celebration), _, _) => {
+                            celebration.draw(canvas, &self.fonts);
+                        }
+                        (None, common::ScreenId::Countdown, _) => {
+                            self.draw_countdown(canvas, current_data);
+                        }
+                        (None, common::ScreenId::Recap, _) => {
+                            self.draw_recap(canvas, current_data);
+                        }
+                        (None, common::ScreenId::Standings, _) | (None, _, None) => {
+                            self.draw_no_games(canvas, current_data);
+                        }
+                        (None, _, Some(_)) if self.games_per_page > 1 => {
+                            self.draw_split_view(canvas, current_data);
+                        }
+                        (None, _, Some(SportData::Golf(golf))) => {
+                            // Favorite golfers are pinned to the leaderboard
+                            golf.draw_leaderboard(
+                                canvas,
+                                &self.fonts,
+                                &self.settings.timezone,
+                                &self.settings.get_focus_teams(common::ScreenId::Golf),
+                            );
+                        }
+                        (None, _, Some(active_game)) => {
                             active_game.get_inner().draw_screen(
                                 canvas,
                                 &self.fonts,
@@ -438,16 +1142,16 @@ impl matrix::ScreenProvider for AWSScreen {
                                 &self.settings.timezone,
                             );
                         }
-                        None => {
-                            self.draw_no_games(canvas);
-                        }
+                    }
+                    if current_data.stale_since.is_some() {
+                        draw_stale_marker(canvas);
                     }
                 } else {
                     self.draw_refresh(canvas); // Data is out of date, draw refresh
                 }
             }
-            ReceivedData::Error => {
-                self.draw_error(canvas);
+            ReceivedData::Error(e) => {
+                self.draw_error(canvas, e);
             }
             ReceivedData::None => {
                 self.draw_refresh(canvas);
@@ -470,10 +1174,22 @@ impl matrix::ScreenProvider for AWSScreen {
         self
     }
 
-    fn has_priority(&mut self, _power_mode: &common::AutoPowerMode) -> bool {
+    fn has_priority(&mut self, id: &common::ScreenId, power_mode: &common::AutoPowerMode) -> bool {
         self.process(); // Ensure we fetch any updated games
-        match &self.data {
-            ReceivedData::Valid(data, _error_count) => {
+        match (&self.data, id) {
+            (ReceivedData::Valid(data, _error_count), common::ScreenId::Countdown) => {
+                power_mode == &common::AutoPowerMode::Countdown
+                    && countdown::should_wake(&data.get_next_games(&self.settings.favorite_teams))
+            }
+            (ReceivedData::Valid(data, _error_count), common::ScreenId::Recap) => {
+                power_mode == &common::AutoPowerMode::Recap
+                    && recap::should_wake(
+                        &recap::get_recap_games(&data.recap, &self.settings),
+                        &self.settings.timezone,
+                    )
+            }
+            (_, common::ScreenId::Countdown) | (_, common::ScreenId::Recap) => false,
+            (ReceivedData::Valid(data, _error_count), _) => {
                 data.games
                     .iter()
                     .filter(|game| {
@@ -483,8 +1199,124 @@ impl matrix::ScreenProvider for AWSScreen {
                     .count()
                     > data.filtered_games.len()
             }
-            ReceivedData::Error => true,
+            (ReceivedData::Error(_), _) => true,
             _ => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_lenient_parsing() {
+        let demo: serde_json::Value =
+            serde_json::from_str(include_str!("../assets/demo/demo_scores.json")).unwrap();
+        let golf = demo["data"]["games"][0].clone();
+        let mut bad_status = golf.clone();
+        bad_status["common"]["status"] = serde_json::json!("POSTPONED");
+        let mut unknown_sport = golf.clone();
+        unknown_sport["type"] = serde_json::json!("Lacrosse");
+        unknown_sport["common"]["sport_id"] = serde_json::json!(12);
+        let mut other_unknown_sport = unknown_sport.clone();
+        other_unknown_sport["common"]["sport_id"] = serde_json::json!(13);
+        let response = serde_json::json!({
+            "data": { "games": [golf, bad_status, unknown_sport, other_unknown_sport] }
+        });
+
+        let data = process_data_string(&response.to_string()).unwrap();
+        assert_eq!(data.games.len(), 3);
+        assert!(matches!(data.games[0], SportData::Golf(_)));
+        match &data.games[1] {
+            SportData::Generic(generic) => {
+                assert_eq!(generic.common.sport_id, common::ScreenId::Smart)
+            }
+            game => panic!("Unexpected game {:?}", game),
+        }
+        // Same game id, different unknown sports
+        assert_eq!(data.games[1].get_ref().sport_id, 12);
+        assert_ne!(data.games[1].get_ref(), data.games[2].get_ref());
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        let refresh = config::RefreshConfig::default();
+        for _ in 0..20 {
+            let delay = backoff_delay(&refresh, 1);
+            assert!(delay >= refresh.retry_initial / 2 && delay <= refresh.retry_initial);
+            let delay = backoff_delay(&refresh, 3);
+            assert!(delay >= refresh.retry_initial * 2 && delay <= refresh.retry_initial * 4);
+            // Jitter never pushes the delay past the cap, even after many failures
+            let delay = backoff_delay(&refresh, u32::MAX);
+            assert!(delay >= refresh.retry_max / 2 && delay <= refresh.retry_max);
+        }
+    }
+
+    #[test]
+    fn test_score_cache_age() {
+        let cache_path = std::env::temp_dir().join("scoreboard_test_score_cache.json");
+        let response = r#"{"data": {"games": []}}"#;
+        write_score_cache(&cache_path, response, &CacheValidators::default());
+        let (mut data, _validators) = read_score_cache(&cache_path).unwrap();
+        assert!(data.is_usable_cache());
+        data.stale_since = Some(Utc::now() - chrono::Duration::hours(MAX_CACHE_AGE_HOURS + 1));
+        assert!(!data.is_usable_cache());
+        data.mark_current();
+        assert!(!data.is_usable_cache());
+
+        let old_cache = ScoreCache {
+            timestamp: Utc::now().timestamp() - (MAX_CACHE_AGE_HOURS + 1) * 60 * 60,
+            response: response.to_string(),
+            validators: CacheValidators::default(),
+        };
+        fs::write(&cache_path, serde_json::to_string(&old_cache).unwrap()).unwrap();
+        assert!(read_score_cache(&cache_path).is_none());
+        let _ = fs::remove_file(cache_path);
+    }
+
+    #[test]
+    fn test_event_stream() {
+        // A stand-in for the score stream that sends a couple of events and hangs up
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/stream", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\n\
+                    Content-Type: text/event-stream\r\n\
+                    Connection: close\r\n\r\n\
+                    : keepalive\n\n\
+                    data: {\"removed\": [{\"sport_id\": 0, \"id\": 4}]}\n\n\
+                    data: not json\n\n",
+                )
+                .unwrap();
+        });
+
+        let mut events = Vec::new();
+        read_event_stream(&url, |event| events.push(event)).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], StreamEvent::Connected));
+        assert!(matches!(events[1], StreamEvent::Keepalive));
+        match &events[2] {
+            StreamEvent::Delta(delta) => {
+                assert!(delta.games.is_empty());
+                assert_eq!(
+                    delta.removed,
+                    vec![GameRef {
+                        sport_id: common::ScreenId::Hockey as u16,
+                        id: 4
+                    }]
+                );
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// League tables, sent alongside games in the `standings` array of the payload
+use crate::common::{self, ScreenId};
+use crate::game;
+use crate::matrix;
+
+use chrono::Utc;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+const TEAMS_PER_PAGE: usize = 4;
+const PAGE_TIME_SECONDS: i64 = 5;
+const RECORD_X: i32 = 23; // Just past the abbreviation
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StandingsRow {
+    #[serde(deserialize_with = "game::u32_from_string")]
+    pub team_id: u32,
+    pub abbreviation: String,
+    pub rank: u8,
+    pub wins: u16,
+    pub losses: u16,
+    pub ot_losses: Option<u16>, // Overtime losses in hockey, draws or ties elsewhere
+    pub points: Option<u16>,
+    pub games_back: Option<String>, // Like 2.5, or - for the leader
+}
+
+impl StandingsRow {
+    fn get_record_text(&self) -> String {
+        match self.ot_losses {
+            Some(ot_losses) => format!("{}-{}-{}", self.wins, self.losses, ot_losses),
+            None => self.get_short_record_text(),
+        }
+    }
+
+    // For when the full record would run into the abbreviation
+    fn get_short_record_text(&self) -> String {
+        format!("{}-{}", self.wins, self.losses)
+    }
+
+    // Points for leagues that use them, games back for the rest
+    fn get_standing_text(&self) -> String {
+        match (self.points, &self.games_back) {
+            (Some(points), _) => points.to_string(),
+            (None, Some(games_back)) => games_back.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Division {
+    pub name: String, // A division, conference or group
+    pub teams: Vec<StandingsRow>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LeagueStandings {
+    pub sport_id: ScreenId,
+    pub divisions: Vec<Division>,
+}
+
+// Like games, a league that doesn't parse is skipped instead of failing the whole payload
+pub fn parse_standings(values: Vec<serde_json::Value>) -> Vec<LeagueStandings> {
+    values
+        .into_iter()
+        .filter_map(|value| match serde_json::from_value(value) {
+            Ok(standings) => Some(standings),
+            Err(e) => {
+                warn!("Skipping standings that failed to parse: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+struct Page<'a> {
+    sport_id: ScreenId,
+    division: &'a Division,
+    teams: &'a [StandingsRow],
+    number: usize, // Within the division, starting at 1
+    total: usize,
+}
+
+fn get_pages<'a>(standings: &'a [LeagueStandings], leagues: &HashSet<ScreenId>) -> Vec<Page<'a>> {
+    standings
+        .iter()
+        .filter(|league| leagues.contains(&league.sport_id))
+        .flat_map(|league| {
+            league.divisions.iter().flat_map(move |division| {
+                let total = (division.teams.len() + TEAMS_PER_PAGE - 1) / TEAMS_PER_PAGE;
+                division
+                    .teams
+                    .chunks(TEAMS_PER_PAGE)
+                    .enumerate()
+                    .map(move |(i, teams)| Page {
+                        sport_id: league.sport_id,
+                        division,
+                        teams,
+                        number: i + 1,
+                        total,
+                    })
+            })
+        })
+        .collect()
+}
+
+// Pages through every division of `leagues`, returns false if there is nothing to show
+pub fn draw_standings(
+    canvas: &mut rpi_led_matrix::LedCanvas,
+    font: &matrix::Font,
+    standings: &[LeagueStandings],
+    leagues: &HashSet<ScreenId>,
+    settings: &common::ScoreboardSettingsData,
+) -> bool {
+    let pages = get_pages(standings, leagues);
+    if pages.is_empty() {
+        return false;
+    }
+    let page = &pages[(Utc::now().timestamp() / PAGE_TIME_SECONDS) as usize % pages.len()];
+    let (width, _height) = canvas.canvas_size();
+    let white = common::new_color(255, 255, 255);
+    let grey = common::new_color(150, 150, 150);
+    let yellow = common::new_color(255, 255, 0);
+    let green = common::new_color(52, 162, 35);
+    let row_height = font.dimensions.height + 1;
+
+    canvas.draw_text(
+        &font.led_font,
+        &page.division.name.to_ascii_uppercase(),
+        1,
+        font.dimensions.height,
+        &green,
+        0,
+        false,
+    );
+    if page.total > 1 {
+        let page_text = format!("{}/{}", page.number, page.total);
+        canvas.draw_text(
+            &font.led_font,
+            &page_text,
+            width - font.get_text_dimensions(&page_text).width,
+            font.dimensions.height,
+            &grey,
+            0,
+            false,
+        );
+    }
+
+    // Records are right aligned against the widest standing on the page
+    let standing_width = page
+        .teams
+        .iter()
+        .map(|team| font.get_text_dimensions(&team.get_standing_text()).width)
+        .max()
+        .unwrap_or(0);
+    let record_right = width - standing_width - 3;
+
+    let favorites = settings.get_focus_teams(page.sport_id);
+    for (i, team) in page.teams.iter().enumerate() {
+        let baseline = (i as i32 + 2) * row_height;
+        let color = match favorites.contains(&team.team_id) {
+            true => &yellow,
+            false => &white,
+        };
+        let rank = team.rank.to_string();
+        let standing = team.get_standing_text();
+        let mut record = team.get_record_text();
+        if record_right - font.get_text_dimensions(&record).width < RECORD_X {
+            record = team.get_short_record_text();
+        }
+        canvas.draw_text(
+            &font.led_font,
+            &rank,
+            8 - font.get_text_dimensions(&rank).width,
+            baseline,
+            &grey,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &team.abbreviation.to_ascii_uppercase(),
+            10,
+            baseline,
+            color,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &record,
+            record_right - font.get_text_dimensions(&record).width,
+            baseline,
+            color,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &standing,
+            width - font.get_text_dimensions(&standing).width,
+            baseline,
+            color,
+            0,
+            false,
+        );
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standings() {
+        let data = r#"[
+            {
+                "sport_id": 0,
+                "divisions": [{
+                    "name": "Central",
+                    "teams": [
+                        {"team_id": "25", "abbreviation": "DAL", "rank": 1, "wins": 52, "losses": 21, "ot_losses": 9, "points": 113},
+                        {"team_id": "52", "abbreviation": "WPG", "rank": 2, "wins": 52, "losses": 24, "ot_losses": 6, "points": 110},
+                        {"team_id": "19", "abbreviation": "STL", "rank": 5, "wins": 43, "losses": 33, "ot_losses": 6, "points": 92},
+                        {"team_id": "30", "abbreviation": "MIN", "rank": 6, "wins": 39, "losses": 34, "ot_losses": 9, "points": 87},
+                        {"team_id": "16", "abbreviation": "CHI", "rank": 8, "wins": 23, "losses": 53, "ot_losses": 6, "points": 52}
+                    ]
+                }]
+            },
+            {
+                "sport_id": 1,
+                "divisions": [{
+                    "name": "AL East",
+                    "teams": [{"team_id": "147", "abbreviation": "NYY", "rank": 1, "wins": 94, "losses": 68, "games_back": "-"}]
+                }]
+            },
+            {"sport_id": 1, "divisions": "not a list"}
+        ]"#;
+
+        let standings = parse_standings(serde_json::from_str(data).unwrap());
+        assert_eq!(standings.len(), 2);
+        let hockey_team = &standings[0].divisions[0].teams[0];
+        assert_eq!(hockey_team.get_record_text(), "52-21-9");
+        assert_eq!(hockey_team.get_short_record_text(), "52-21");
+        assert_eq!(hockey_team.get_standing_text(), "113");
+        let baseball_team = &standings[1].divisions[0].teams[0];
+        assert_eq!(baseball_team.get_record_text(), "94-68");
+        assert_eq!(baseball_team.get_standing_text(), "-");
+
+        let hockey: HashSet<ScreenId> = vec![ScreenId::Hockey].into_iter().collect();
+        let pages = get_pages(&standings, &hockey);
+        assert_eq!(pages.len(), 2);
+        assert_eq!((pages[1].number, pages[1].total), (2, 2));
+        assert_eq!(pages[1].teams.len(), 1);
+    }
+}
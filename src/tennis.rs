@@ -0,0 +1,227 @@
+use crate::aws_screen;
+use crate::common;
+use crate::game;
+use crate::matrix;
+
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TennisPlayer {
+    #[serde(deserialize_with = "game::u32_from_string")]
+    pub id: u32, // Used as the team id for favorites
+    pub display_name: String,
+    #[serde(default)]
+    pub sets: Vec<u8>, // Games won in each set, the last one is the current set
+    pub game_score: Option<String>, // 0, 15, 30, 40 or AD, or tiebreak points during a tiebreak
+    #[serde(default)]
+    pub serving: bool,
+}
+
+// The common teams are placeholders, like golf, since a match is between players
+#[derive(Deserialize, Debug, Clone)]
+pub struct TennisGame {
+    pub common: game::CommonGameData,
+    pub name: String, // Tournament name
+    pub players: Vec<TennisPlayer>,
+    #[serde(default)]
+    pub tiebreak: bool,
+}
+
+impl Ord for TennisGame {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.common.cmp(&other.common)
+    }
+}
+
+impl PartialOrd for TennisGame {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for TennisGame {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.common,) == (&other.common,)
+    }
+}
+
+impl Eq for TennisGame {}
+
+impl game::Sport for TennisGame {
+    fn get_common(&self) -> &game::CommonGameData {
+        &self.common
+    }
+
+    fn involves_team(&self, target_team: u32) -> bool {
+        self.players.iter().any(|player| player.id == target_team)
+    }
+}
+
+const SET_COLUMN_WIDTH: i32 = 5;
+const GAME_COLUMN_WIDTH: i32 = 10;
+
+impl TennisGame {
+    fn draw_player(
+        &self,
+        player: &TennisPlayer,
+        y_offset: i32,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font: &matrix::Font,
+    ) {
+        let (width, _height) = canvas.canvas_size();
+        let white = common::new_color(255, 255, 255);
+        let grey = common::new_color(120, 120, 120);
+        let yellow = common::new_color(255, 255, 0);
+        let green = common::new_color(52, 162, 35);
+        let baseline = y_offset + font.dimensions.height;
+        let is_active = self.common.is_active_game();
+
+        // Serve indicator
+        if is_active && player.serving {
+            matrix::draw_rectangle(canvas, (0, y_offset + 2), (2, y_offset + 4), &yellow);
+        }
+
+        // Current game score in its own column on the right
+        let game_column = width - GAME_COLUMN_WIDTH;
+        if is_active {
+            if let Some(game_score) = &player.game_score {
+                let score_width = font.get_text_dimensions(game_score).width;
+                let color = if self.tiebreak { &yellow } else { &green };
+                canvas.draw_text(
+                    &font.led_font,
+                    game_score,
+                    width - 1 - score_width,
+                    baseline,
+                    color,
+                    0,
+                    false,
+                );
+            }
+        }
+
+        // One column per set, the set in progress is highlighted
+        let sets_start = game_column - SET_COLUMN_WIDTH * player.sets.len() as i32;
+        for (i, games) in player.sets.iter().enumerate() {
+            let is_current_set = is_active && i == player.sets.len() - 1;
+            canvas.draw_text(
+                &font.led_font,
+                &games.to_string(),
+                sets_start + SET_COLUMN_WIDTH * i as i32,
+                baseline,
+                if is_current_set { &white } else { &grey },
+                0,
+                false,
+            );
+        }
+
+        // Player name, cut short to fit in front of the set columns
+        let max_characters = std::cmp::max((sets_start - 4) / font.dimensions.width, 0) as usize;
+        let name: String = player
+            .display_name
+            .to_ascii_uppercase()
+            .chars()
+            .take(max_characters)
+            .collect();
+        canvas.draw_text(&font.led_font, &name, 3, baseline, &white, 0, false);
+    }
+}
+
+impl aws_screen::AWSScreenType for TennisGame {
+    fn draw_screen(
+        &self,
+        canvas: &mut rpi_led_matrix::LedCanvas,
+        font_book: &matrix::FontBook,
+        _pixels_book: &matrix::PixelBook,
+        timezone: &Tz,
+    ) {
+        let font = &font_book.font4x6;
+        let white = common::new_color(255, 255, 255);
+        let yellow = common::new_color(255, 255, 0);
+        let green = common::new_color(52, 162, 35);
+
+        for (i, player) in self.players.iter().take(2).enumerate() {
+            self.draw_player(
+                player,
+                1 + i as i32 * (font.dimensions.height + 2),
+                canvas,
+                font,
+            );
+        }
+
+        // The event name and ordinal sit in the bottom rows, under the players
+        let (width, height) = canvas.canvas_size();
+        let top_row_height = height - 15;
+        let bottom_row_height = height - 7;
+        let right_indent = width - 1;
+        canvas.draw_text(
+            &font.led_font,
+            &self.name.to_ascii_uppercase(),
+            2,
+            top_row_height + font.dimensions.height,
+            &green,
+            0,
+            false,
+        );
+        canvas.draw_text(
+            &font.led_font,
+            &self.common.get_ordinal_text(timezone),
+            2,
+            bottom_row_height + font.dimensions.height,
+            &white,
+            0,
+            false,
+        );
+        let status_text = match self.common.status {
+            game::GameStatus::End => Some("FINAL"),
+            game::GameStatus::Active if self.tiebreak => Some("TIEBREAK"),
+            _ => None,
+        };
+        if let Some(status_text) = status_text {
+            canvas.draw_text(
+                &font.led_font,
+                status_text,
+                right_indent - font.get_text_dimensions(status_text).width,
+                bottom_row_height + font.dimensions.height,
+                &yellow,
+                0,
+                false,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Sport;
+
+    #[test]
+    fn test_tennis() {
+        let data = r#"{
+            "common": {
+                "sport_id": 8,
+                "home_team": {"id": "0", "display_name": "", "abbreviation": "", "primary_color": "000000", "secondary_color": "000000"},
+                "away_team": {"id": "0", "display_name": "", "abbreviation": "", "primary_color": "000000", "secondary_color": "000000"},
+                "home_score": 0,
+                "away_score": 0,
+                "status": "ACTIVE",
+                "ordinal": "3rd Set",
+                "start_time": "2023-07-16T13:00Z",
+                "id": 1234
+            },
+            "name": "Wimbledon",
+            "players": [
+                {"id": "68", "display_name": "Alcaraz", "sets": [1, 7, 6], "game_score": "AD", "serving": true},
+                {"id": "14", "display_name": "Djokovic", "sets": [6, 6, 1], "game_score": "40"}
+            ]
+        }"#;
+
+        let game: TennisGame = serde_json::from_str(data).unwrap();
+        assert!(game.involves_team(14));
+        assert!(!game.involves_team(0));
+        assert!(!game.players[1].serving);
+        assert!(!game.tiebreak);
+    }
+}
@@ -1,6 +1,8 @@
-use crate::common::{CustomMessage,
-    MESSAGE_PATH, CommandSource, MatrixCommand, ScoreboardSettingsData, ScreenId, WebserverResponse,
+use crate::bundle::{self, ConfigBundle, ImportReport};
+use crate::common::{self, CustomMessage,
+    Profile, ProfileList, CommandSource, MatrixCommand, ScoreboardSettingsData, ScreenId, WebserverResponse,
 };
+use crate::diagnostics::Diagnostics;
 use rocket::config::{Config, Environment};
 use rocket::response::{status, Content};
 use rocket::{get, http::ContentType, post, routes, State};
@@ -37,11 +39,16 @@ struct RebootRequest {
     restart: Option<String>,
     reboot_message: Option<String>,
 }
+#[derive(Deserialize, Serialize, PartialEq, Debug, Clone)]
+struct ProfileRequest {
+    name: String,
+}
 
 struct ServerState {
     sender: mpsc::Sender<MatrixCommand>,
     receiver: mpsc::Receiver<WebserverResponse>,
     file_path: PathBuf,
+    file_writes: common::FileWrites,
 }
 
 impl ServerState {
@@ -49,11 +56,13 @@ impl ServerState {
         sender: mpsc::Sender<MatrixCommand>,
         receiver: mpsc::Receiver<WebserverResponse>,
         file_path: PathBuf,
+        file_writes: common::FileWrites,
     ) -> ServerState {
         ServerState {
             sender,
             receiver,
             file_path,
+            file_writes,
         }
     }
 }
@@ -87,7 +96,10 @@ fn configure(
     let state = state.lock().unwrap();
     (*state)
         .sender
-        .send(MatrixCommand::UpdateSettings(new_settings.into_inner()))
+        .send(MatrixCommand::UpdateSettings {
+            source: CommandSource::Webserver(),
+            settings: new_settings.into_inner(),
+        })
         .unwrap();
     let response = (*state).receiver.recv().unwrap();
     match response {
@@ -215,13 +227,12 @@ fn set_custom_message(
     let state = state.lock().unwrap();
     (*state)
         .sender
-        .send(MatrixCommand::SetCustomMessage(custom_message.clone()))
+        .send(MatrixCommand::SetCustomMessage {
+            source: CommandSource::Webserver(),
+            message: custom_message.clone(),
+        })
         .unwrap();
-    fs::write(
-        (*state).file_path.join(MESSAGE_PATH),
-        serde_json::to_string_pretty(&(custom_message.0)).unwrap(),
-    )
-    .unwrap();
+    common::write_custom_message(&(*state).file_path, &(custom_message.0), &(*state).file_writes).unwrap();
     let response = (*state).receiver.recv().unwrap();
     match response {
         WebserverResponse::SetCustomMessage() => Ok(status::Accepted(None)),
@@ -229,6 +240,134 @@ fn set_custom_message(
     }
 }
 
+#[get("/profiles")]
+fn get_profiles(state: State<Mutex<ServerState>>) -> Result<Content<Json<ProfileList>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    (*state).sender.send(MatrixCommand::GetProfiles()).unwrap();
+    let response = (*state).receiver.recv().unwrap();
+    match response {
+        WebserverResponse::GetProfiles(profiles) => Ok(Content(content, Json(profiles))),
+        _ => Err(status::NotFound("Internal error".to_string())),
+    }
+}
+#[post("/createProfile", format = "json", data = "<profile>")]
+fn create_profile(
+    profile: Json<Profile>,
+    state: State<Mutex<ServerState>>,
+) -> Result<Content<Json<ProfileList>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    (*state)
+        .sender
+        .send(MatrixCommand::CreateProfile(profile.into_inner()))
+        .unwrap();
+    let response = (*state).receiver.recv().unwrap();
+    match response {
+        WebserverResponse::CreateProfile(profiles) => match profiles {
+            Some(profiles) => Ok(Content(content, Json(profiles))),
+            None => Err(status::NotFound("Profile must have a name".to_string())),
+        },
+        _ => Err(status::NotFound("Internal error".to_string())),
+    }
+}
+#[post("/activateProfile", format = "json", data = "<profile_request>")]
+fn activate_profile(
+    profile_request: Json<ProfileRequest>,
+    state: State<Mutex<ServerState>>,
+) -> Result<Content<Json<Arc<ScoreboardSettingsData>>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    (*state)
+        .sender
+        .send(MatrixCommand::ActivateProfile {
+            source: CommandSource::Webserver(),
+            name: profile_request.name.clone(),
+        })
+        .unwrap();
+    let response = (*state).receiver.recv().unwrap();
+    match response {
+        WebserverResponse::ActivateProfile(settings) => match settings {
+            Some(settings) => Ok(Content(content, Json(settings))),
+            None => Err(status::NotFound("Could not find profile".to_string())),
+        },
+        _ => Err(status::NotFound("Internal error".to_string())),
+    }
+}
+#[post("/deleteProfile", format = "json", data = "<profile_request>")]
+fn delete_profile(
+    profile_request: Json<ProfileRequest>,
+    state: State<Mutex<ServerState>>,
+) -> Result<Content<Json<ProfileList>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    (*state)
+        .sender
+        .send(MatrixCommand::DeleteProfile(profile_request.name.clone()))
+        .unwrap();
+    let response = (*state).receiver.recv().unwrap();
+    match response {
+        WebserverResponse::DeleteProfile(profiles) => match profiles {
+            Some(profiles) => Ok(Content(content, Json(profiles))),
+            None => Err(status::NotFound("Could not find profile".to_string())),
+        },
+        _ => Err(status::NotFound("Internal error".to_string())),
+    }
+}
+
+// Secrets are only exported from the CLI, so the API key never leaves the board over HTTP
+#[get("/exportConfig")]
+fn export_config(
+    state: State<Mutex<ServerState>>,
+) -> Result<Content<Json<ConfigBundle>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    match bundle::export_bundle(&(*state).file_path, false) {
+        Ok(bundle) => Ok(Content(content, Json(bundle))),
+        Err(e) => Err(status::NotFound(format!("Failed to export configuration: {}", e))),
+    }
+}
+#[post("/importConfig?<dry_run>", format = "json", data = "<config_bundle>")]
+fn import_config(
+    dry_run: Option<bool>,
+    config_bundle: Json<ConfigBundle>,
+    state: State<Mutex<ServerState>>,
+) -> Result<Content<Json<ImportReport>>, status::NotFound<String>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    // Anyone on the network can reach this, so the API key can only be replaced from the CLI
+    let mut bundle = config_bundle.into_inner();
+    let secrets_ignored = bundle.secrets.take().is_some();
+    let state = state.lock().unwrap();
+    (*state)
+        .sender
+        .send(MatrixCommand::ImportConfig {
+            bundle: Box::new(bundle),
+            dry_run: dry_run.unwrap_or(false),
+        })
+        .unwrap();
+    let response = (*state).receiver.recv().unwrap();
+    match response {
+        WebserverResponse::ImportConfig(report) => match report {
+            Ok(report) => Ok(Content(
+                content,
+                Json(ImportReport {
+                    secrets_ignored,
+                    ..report
+                }),
+            )),
+            Err(e) => Err(status::NotFound(e)),
+        },
+        _ => Err(status::NotFound("Internal error".to_string())),
+    }
+}
+
+#[get("/diagnostics")]
+fn diagnostics(state: State<Mutex<ServerState>>) -> Content<Json<Diagnostics>> {
+    let content = ContentType::parse_flexible("application/json; charset=utf-8").unwrap();
+    let state = state.lock().unwrap();
+    Content(content, Json(Diagnostics::read(&(*state).file_path)))
+}
+
 #[get("/logs")]
 fn logs(state: State<Mutex<ServerState>>) -> Result<String, std::io::Error> {
     let state = state.lock().unwrap();
@@ -362,17 +501,19 @@ pub fn run_webserver(
     sender: mpsc::Sender<MatrixCommand>,
     receiver: mpsc::Receiver<WebserverResponse>,
     file_path: PathBuf,
+    file_writes: common::FileWrites,
+    port: u16,
 ) {
     let config = Config::build(Environment::Production)
         .address("0.0.0.0")
         .log_level(rocket::config::LoggingLevel::Critical)
-        .port(5005)
+        .port(port)
         .workers(1)
         .finalize()
         .unwrap();
 
     rocket::custom(config)
-        .manage(Mutex::new(ServerState::new(sender, receiver, file_path)))
+        .manage(Mutex::new(ServerState::new(sender, receiver, file_path, file_writes)))
         .mount(
             "/",
             routes![
@@ -383,6 +524,7 @@ pub fn run_webserver(
                 set_sport,
                 wifi,
                 logs,
+                diagnostics,
                 show_sync,
                 reboot,
                 reset,
@@ -391,7 +533,13 @@ pub fn run_webserver(
                 version,
                 game_action,
                 get_custom_message,
-                set_custom_message
+                set_custom_message,
+                get_profiles,
+                create_profile,
+                activate_profile,
+                delete_profile,
+                export_config,
+                import_config
             ],
         )
         .launch();